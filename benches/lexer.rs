@@ -0,0 +1,80 @@
+//! Lexer throughput on a generated multi-megabyte script.
+//!
+//! Run with `cargo bench --bench lexer`.
+
+use std::{borrow::Cow, hint::black_box, time::Instant};
+
+use nya_lang::{lexer::Lexer, token::Token};
+
+const SNIPPET: &str = r#"
+// Computes a few things the long way round.
+fn fib(n) {
+    if (n < 2) return n; else return fib(n - 1) + fib(n - 2);
+}
+
+/* Counters and /* nested */ comments. */
+class Counter extends Base {
+    count = 0x_ff;
+
+    constructor(start) { this.count = start; }
+    get value() { return this.count; }
+    inc(step) { this.count += step * 2 >> 1; }
+}
+
+let message = 'total: ';
+for (let i in 0..=1_000) {
+    const c = Counter(i);
+    c.inc(3.25e2);
+    message = f"${message}${c.value} ";
+}
+"#;
+
+const TARGET_SIZE: usize = 4 * 1024 * 1024;
+const ITERATIONS: u32 = 10;
+
+fn main() {
+    let input = SNIPPET.repeat(TARGET_SIZE / SNIPPET.len() + 1);
+    let lexer = Lexer::new(input);
+    let megabytes = lexer.input_ref().len() as f64 / (1024.0 * 1024.0);
+
+    // Tokens are collected, as a parser would keep them around.
+    measure("borrowed", megabytes, || {
+        black_box(lexer.tokens().map(Result::unwrap).collect::<Vec<_>>()).len()
+    });
+    measure("owned", megabytes, || {
+        black_box(lexer.tokens().map(|token| owned(token.unwrap())).collect::<Vec<_>>()).len()
+    });
+}
+
+// Gives the token a lexeme of its own the way the lexer did before lexemes were
+// borrowed from the source, pushing every char into a new `String`.
+fn owned(token: Token<'_>) -> Token<'static> {
+    let mut lexeme = String::new();
+
+    for ch in token.lexeme.chars() {
+        lexeme.push(ch);
+    }
+
+    Token {
+        lexeme: Cow::Owned(lexeme),
+        span: token.span,
+        ..Token::new(token.token_type, "")
+    }
+}
+
+fn measure(name: &str, megabytes: f64, lex: impl Fn() -> usize) {
+    let mut tokens = 0;
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        tokens = lex();
+    }
+
+    let seconds = start.elapsed().as_secs_f64() / ITERATIONS as f64;
+
+    println!(
+        "{name:>8}: lexed {megabytes:.1} MiB ({tokens} tokens) in {:.1} ms, {:.1} MiB/s",
+        seconds * 1000.0,
+        megabytes / seconds,
+    );
+}
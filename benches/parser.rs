@@ -0,0 +1,95 @@
+//! Heap allocations made parsing a generated script into an `Arena`, and into
+//! `Statement` trait objects through it.
+//!
+//! Run with `cargo bench --bench parser`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use nya_lang::{lexer::Lexer, parser::Parser};
+
+const SNIPPET: &str = r#"
+// Computes a few things the long way round.
+fn fib(n) {
+    if (n < 2) { return n; } else { return fib(n - 1) + fib(n - 2); }
+}
+
+class Counter extends Base {
+    count = 0xff;
+
+    constructor(start) { this.count = start; }
+    get value() { return this.count; }
+    inc(step) { this.count += step * 2 >> 1; }
+}
+
+let message = 'total: ';
+for (let i in 0..=1_000) {
+    let c = Counter(i);
+    c.inc(3.25e2);
+    message = f"${message}${c.value} ";
+}
+"#;
+
+const TARGET_SIZE: usize = 1024 * 1024;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        // Only the growth, as the old block is given back.
+        ALLOCATED_BYTES.fetch_add(new_size.saturating_sub(layout.size()), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let input = SNIPPET.repeat(TARGET_SIZE / SNIPPET.len() + 1);
+    let megabytes = input.len() as f64 / (1024.0 * 1024.0);
+    let parser = Parser::new(Lexer::new(input));
+
+    measure("arena", megabytes, || {
+        black_box(parser.parse_arena().unwrap()).statements().len()
+    });
+    measure("boxed", megabytes, || {
+        black_box(parser.parse().unwrap()).statements().len()
+    });
+}
+
+fn measure(name: &str, megabytes: f64, parse: impl Fn() -> usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    let statements = parse();
+
+    let seconds = start.elapsed().as_secs_f64();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+
+    println!(
+        "{name:>6}: parsed {megabytes:.1} MiB ({statements} statements) in {:.1} ms, {allocations} allocations, {:.1} MiB allocated",
+        seconds * 1000.0,
+        bytes as f64 / (1024.0 * 1024.0),
+    );
+}
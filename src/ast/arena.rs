@@ -0,0 +1,383 @@
+use std::rc::Rc;
+
+use crate::{
+    span::Span,
+    token::{Token, TokenType},
+};
+
+use super::{
+    Ast, BinaryOp, Expression, Literal, MethodKind, Node, NodeId, Statement, UnaryOp,
+};
+
+// The same tree as the `Expression` and `Statement` trait objects, as closed enums
+// whose children are the `NodeId`s of other nodes in the `Arena`.
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    Identifier(String),
+    Literal(Literal),
+    Binary { left: NodeId, op: BinaryOp, right: NodeId },
+    Unary { op: UnaryOp, right: NodeId },
+    If { condition: NodeId, consequence: NodeId, alternative: Option<NodeId> },
+    Call { callee: NodeId, arguments: Vec<NodeId> },
+    Member { object: NodeId, property: NodeId },
+    Index { object: NodeId, index: NodeId },
+    FunctionLiteral { parameters: Vec<NodeId>, body: NodeId },
+    Range { start: NodeId, end: NodeId, inclusive: bool },
+    FString(Vec<FStringPart>),
+}
+
+#[derive(Debug, Clone)]
+pub enum FStringPart {
+    Text(String),
+    Expression(NodeId),
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
+    Let { identifier: NodeId, expression: Option<NodeId> },
+    Const { identifier: NodeId, expression: NodeId },
+    Return(Option<NodeId>),
+    Expr(NodeId),
+    Block(Vec<NodeId>),
+    Function { name: NodeId, parameters: Vec<NodeId>, body: NodeId },
+    While { condition: NodeId, body: NodeId },
+    For { initializer: Option<NodeId>, condition: Option<NodeId>, update: Option<NodeId>, body: NodeId },
+    ForIn { variable: NodeId, iterable: NodeId, body: NodeId },
+    Break,
+    Continue,
+    Class { name: NodeId, superclass: Option<NodeId>, members: Vec<ClassMember> },
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub enum ClassMember {
+    Method(Method),
+    Property(Property),
+}
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub name: NodeId,
+    pub kind: MethodKind,
+    pub is_static: bool,
+    pub parameters: Vec<NodeId>,
+    pub body: NodeId,
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: NodeId,
+    pub value: Option<NodeId>,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    Expression(ExprKind),
+    Statement(StmtKind),
+}
+
+impl From<ExprKind> for NodeKind {
+    fn from(value: ExprKind) -> Self {
+        NodeKind::Expression(value)
+    }
+}
+
+impl From<StmtKind> for NodeKind {
+    fn from(value: StmtKind) -> Self {
+        NodeKind::Statement(value)
+    }
+}
+
+/// Token a node starts at, with its text left in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenRef {
+    pub token_type: TokenType,
+    pub span: Option<Span>,
+}
+
+impl From<&Token<'_>> for TokenRef {
+    fn from(token: &Token<'_>) -> Self {
+        Self { token_type: token.token_type, span: token.span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArenaNode {
+    pub id: NodeId,
+    pub kind: NodeKind,
+    pub token: Option<TokenRef>,
+    pub span: Option<Span>,
+}
+
+/// Every node of a program in one allocation, where node `id` is stored at `id - 1`.
+/// Tokens are borrowed from the source the arena was parsed from.
+#[derive(Debug, Clone, Default)]
+pub struct Arena<'src> {
+    source: &'src str,
+    nodes: Vec<ArenaNode>,
+    statements: Vec<NodeId>,
+}
+
+impl<'src> Arena<'src> {
+    pub fn new(source: &'src str) -> Self {
+        Self { source, ..Default::default() }
+    }
+
+    /// Stores a node under the next free id.
+    pub(crate) fn alloc(&mut self, kind: impl Into<NodeKind>, token: Option<TokenRef>, span: Option<Span>) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32 + 1);
+        self.nodes.push(ArenaNode { id, kind: kind.into(), token, span });
+
+        id
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&ArenaNode> {
+        (id.0 as usize).checked_sub(1).and_then(|index| self.nodes.get(index))
+    }
+
+    pub fn expression(&self, id: NodeId) -> Option<&ExprKind> {
+        match self.get(id).map(|node| &node.kind) {
+            Some(NodeKind::Expression(kind)) => Some(kind),
+            _ => None,
+        }
+    }
+
+    pub fn statement(&self, id: NodeId) -> Option<&StmtKind> {
+        match self.get(id).map(|node| &node.kind) {
+            Some(NodeKind::Statement(kind)) => Some(kind),
+            _ => None,
+        }
+    }
+
+    pub fn token(&self, id: NodeId) -> Option<Token<'src>> {
+        self.get(id).and_then(|node| node.token).map(|token| token_of(self.source, token))
+    }
+
+    pub fn span(&self, id: NodeId) -> Option<Span> {
+        self.get(id).and_then(|node| node.span)
+    }
+
+    /// All nodes in id order, including the ones of statements a recovering
+    /// parse dropped.
+    pub fn nodes(&self) -> &[ArenaNode] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Top level statements of the program.
+    pub fn statements(&self) -> &[NodeId] {
+        &self.statements
+    }
+
+    pub(crate) fn set_statements(&mut self, statements: Vec<NodeId>) {
+        self.statements = statements;
+    }
+
+    /// Converts the program into trait objects, keeping the ids.
+    pub fn into_ast(self) -> Ast {
+        let mut lowering = Lowering::new(self.source, self.nodes);
+        let statements = self.statements.into_iter()
+            .map(|id| lowering.statement(id))
+            .collect();
+
+        Ast::new(statements)
+    }
+
+    pub(crate) fn into_expression(self, id: NodeId) -> Box<dyn Expression> {
+        Lowering::new(self.source, self.nodes).expression(id)
+    }
+
+    pub(crate) fn into_statement(self, id: NodeId) -> Box<dyn Statement> {
+        Lowering::new(self.source, self.nodes).statement(id)
+    }
+}
+
+fn token_of(source: &str, token: TokenRef) -> Token<'_> {
+    match token.span {
+        Some(span) => Token::new_with_span(token.token_type, &source[span.start..span.end()], span),
+        None => Token::new(token.token_type, ""),
+    }
+}
+
+// Moves nodes out of the arena into boxes. Arenas are only built by the parser, so
+// every node has one parent at most and is of the kind that parent expects.
+struct Lowering<'src> {
+    source: &'src str,
+    nodes: Vec<Option<ArenaNode>>,
+}
+
+impl<'src> Lowering<'src> {
+    fn new(source: &'src str, nodes: Vec<ArenaNode>) -> Self {
+        Self { source, nodes: nodes.into_iter().map(Some).collect() }
+    }
+
+    // Takes the node out along with an owned copy of its token.
+    fn take(&mut self, id: NodeId) -> (NodeId, NodeKind, Option<Token<'static>>, Option<Span>) {
+        let ArenaNode { id, kind, token, span } = (id.0 as usize).checked_sub(1)
+            .and_then(|index| self.nodes.get_mut(index))
+            .and_then(Option::take)
+            .unwrap_or_else(|| panic!("node {} is missing or shared", id.0));
+        let token = token.map(|token| token_of(self.source, token).into_owned());
+
+        (id, kind, token, span)
+    }
+
+    fn expressions(&mut self, ids: Vec<NodeId>) -> Vec<Box<dyn Expression>> {
+        ids.into_iter().map(|id| self.expression(id)).collect()
+    }
+
+    fn expression(&mut self, id: NodeId) -> Box<dyn Expression> {
+        use super::{Binary, Call, FString, FunctionLiteral, Identifier, If, Index, Member, Range, Unary};
+
+        let (id, kind, token, span) = self.take(id);
+        let NodeKind::Expression(kind) = kind else {
+            panic!("node {} is not an expression", id.0);
+        };
+
+        match kind {
+            ExprKind::Identifier(name) => Node::new(id, Identifier(name), token, span).into(),
+            ExprKind::Literal(literal) => Node::new(id, literal, token, span).into(),
+            ExprKind::Binary { left, op, right } => {
+                let expression = Binary { left: self.expression(left), op, right: self.expression(right) };
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::Unary { op, right } => {
+                let expression = Unary { op, right: self.expression(right) };
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::If { condition, consequence, alternative } => {
+                let expression = If::new(
+                    self.expression(condition),
+                    self.statement(consequence),
+                    alternative.map(|alternative| self.statement(alternative)),
+                );
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::Call { callee, arguments } => {
+                let expression = Call::new(self.expression(callee), self.expressions(arguments));
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::Member { object, property } => {
+                let expression = Member::new(self.expression(object), self.expression(property));
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::Index { object, index } => {
+                let expression = Index::new(self.expression(object), self.expression(index));
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::FunctionLiteral { parameters, body } => {
+                let expression = FunctionLiteral::new(self.expressions(parameters), Rc::new(self.statement(body)));
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::Range { start, end, inclusive } => {
+                let expression = Range::new(self.expression(start), self.expression(end), inclusive);
+                Node::new(id, expression, token, span).into()
+            }
+            ExprKind::FString(parts) => {
+                let parts = parts.into_iter()
+                    .map(|part| match part {
+                        FStringPart::Text(text) => super::FStringPart::Text(text),
+                        FStringPart::Expression(expression) => super::FStringPart::Expression(self.expression(expression)),
+                    })
+                    .collect();
+                Node::new(id, FString::new(parts), token, span).into()
+            }
+        }
+    }
+
+    fn statement(&mut self, id: NodeId) -> Box<dyn Statement> {
+        use super::{Block, Break, Class, Const, Continue, ErrorStatement, Expr, For, ForIn, Function, Let, Return, While};
+
+        let (id, kind, token, span) = self.take(id);
+        let NodeKind::Statement(kind) = kind else {
+            panic!("node {} is not a statement", id.0);
+        };
+
+        match kind {
+            StmtKind::Let { identifier, expression } => {
+                let statement = Let::new(self.expression(identifier), expression.map(|expression| self.expression(expression)));
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::Const { identifier, expression } => {
+                let statement = Const::new(self.expression(identifier), self.expression(expression));
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::Return(expression) => {
+                let statement = Return::new(expression.map(|expression| self.expression(expression)));
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::Expr(expression) => {
+                let statement = Expr::new(self.expression(expression));
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::Block(statements) => {
+                let statements = statements.into_iter().map(|statement| self.statement(statement)).collect();
+                Node::new(id, Block::new(statements), token, span).into()
+            }
+            StmtKind::Function { name, parameters, body } => {
+                let statement = Function::new(
+                    self.expression(name),
+                    self.expressions(parameters),
+                    Rc::new(self.statement(body)),
+                );
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::While { condition, body } => {
+                let statement = While::new(self.expression(condition), self.statement(body));
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::For { initializer, condition, update, body } => {
+                let statement = For::new(
+                    initializer.map(|initializer| self.statement(initializer)),
+                    condition.map(|condition| self.expression(condition)),
+                    update.map(|update| self.expression(update)),
+                    self.statement(body),
+                );
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::ForIn { variable, iterable, body } => {
+                let statement = ForIn::new(self.expression(variable), self.expression(iterable), self.statement(body));
+                Node::new(id, statement, token, span).into()
+            }
+            StmtKind::Break => Node::new(id, Break, token, span).into(),
+            StmtKind::Continue => Node::new(id, Continue, token, span).into(),
+            StmtKind::Class { name, superclass, members } => {
+                let name = self.expression(name);
+                let superclass = superclass.map(|superclass| self.expression(superclass));
+                let members = members.into_iter()
+                    .map(|member| self.class_member(member))
+                    .collect();
+                Node::new(id, Class::new(name, superclass, members), token, span).into()
+            }
+            StmtKind::Error => Node::new(id, ErrorStatement, token, span).into(),
+        }
+    }
+
+    fn class_member(&mut self, member: ClassMember) -> super::ClassMember {
+        match member {
+            ClassMember::Method(Method { name, kind, is_static, parameters, body }) => {
+                super::ClassMember::Method(super::Method {
+                    name: self.expression(name),
+                    kind,
+                    is_static,
+                    parameters: self.expressions(parameters),
+                    body: Rc::new(self.statement(body)),
+                })
+            }
+            ClassMember::Property(Property { name, value }) => {
+                super::ClassMember::Property(super::Property {
+                    name: self.expression(name),
+                    value: value.map(|value| Rc::new(self.expression(value))),
+                })
+            }
+        }
+    }
+}
@@ -1,17 +1,19 @@
+use std::rc::Rc;
+
 use super::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Identifier(pub String);
 
-impl Into<Box<dyn Expression>> for Identifier {
-    fn into(self) -> Box<dyn Expression> {
-        Box::new(self)
+impl From<Identifier> for Box<dyn Expression> {
+    fn from(value: Identifier) -> Self {
+        Box::new(value)
     }
 }
 
-impl ToString for Identifier {
-    fn to_string(&self) -> String {
-        self.0.clone()
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -19,19 +21,28 @@ impl Expression for Identifier {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
         visitor.visit_identifier(self);
     }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Identifier(self)
+    }
+
+    fn as_identifier(&self) -> Option<&Identifier> {
+        Some(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Literal {
     Int(i64),
     Str(String),
     Bool(bool),
     Float(f64),
+    Null,
 }
 
-impl Into<Box<dyn Expression>> for Literal {
-    fn into(self) -> Box<dyn Expression> {
-        Box::new(self)
+impl From<Literal> for Box<dyn Expression> {
+    fn from(value: Literal) -> Self {
+        Box::new(value)
     }
 }
 
@@ -39,18 +50,22 @@ impl Expression for Literal {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
         visitor.visit_literal(self);
     }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Literal(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Binary {
     pub left: Box<dyn Expression>,
     pub op: BinaryOp,
     pub right: Box<dyn Expression>,
 }
 
-impl Into<Box<dyn Expression>> for Binary {
-    fn into(self) -> Box<dyn Expression> {
-        Box::new(self)
+impl From<Binary> for Box<dyn Expression> {
+    fn from(value: Binary) -> Self {
+        Box::new(value)
     }
 }
 
@@ -58,9 +73,13 @@ impl Expression for Binary {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
         visitor.visit_binary(self)
     }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Binary(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
     Plus,
     Minus,
@@ -86,22 +105,24 @@ pub enum BinaryOp {
     AssignBitXor,
     AssignShiftLeft,
     AssignShiftRight,
+    AssignUnsignedShiftRight,
     BitOr,
     BitAnd,
     BitXor,
     ShiftLeft,
     ShiftRight,
+    UnsignedShiftRight,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Unary {
     pub op: UnaryOp,
     pub right: Box<dyn Expression>,
 }
 
-impl Into<Box<dyn Expression>> for Unary {
-    fn into(self) -> Box<dyn Expression> {
-        Box::new(self)
+impl From<Unary> for Box<dyn Expression> {
+    fn from(value: Unary) -> Self {
+        Box::new(value)
     }
 }
 
@@ -109,25 +130,95 @@ impl Expression for Unary {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
         visitor.visit_unary(self)
     }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Unary(self)
+    }
+}
+
+impl BinaryOp {
+    pub fn is_assignment(&self) -> bool {
+        use BinaryOp::*;
+
+        matches!(
+            self,
+            Assign | AssignPlus | AssignMinus | AssignMult | AssignDiv | AssignMod | AssignBitAnd | AssignBitOr
+                | AssignBitXor | AssignShiftLeft | AssignShiftRight | AssignUnsignedShiftRight
+        )
+    }
 }
 
-#[derive(Debug)]
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use BinaryOp::*;
+
+        let sign = match self {
+            Plus => "+",
+            Minus => "-",
+            Eq => "==",
+            Neq => "!=",
+            And => "&&",
+            Or => "||",
+            Gt => ">",
+            Gte => ">=",
+            Lt => "<",
+            Lte => "<=",
+            Mult => "*",
+            Div => "/",
+            Mod => "%",
+            Assign => "=",
+            AssignPlus => "+=",
+            AssignMinus => "-=",
+            AssignMult => "*=",
+            AssignDiv => "/=",
+            AssignMod => "%=",
+            AssignBitAnd => "&=",
+            AssignBitOr => "|=",
+            AssignBitXor => "^=",
+            AssignShiftLeft => "<<=",
+            AssignShiftRight => ">>=",
+            AssignUnsignedShiftRight => ">>>=",
+            BitOr => "|",
+            BitAnd => "&",
+            BitXor => "^",
+            ShiftLeft => "<<",
+            ShiftRight => ">>",
+            UnsignedShiftRight => ">>>",
+        };
+
+        write!(f, "{sign}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnaryOp {
     Minus,
     Not,
     BitNot,
 }
 
-#[derive(Debug)]
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self {
+            UnaryOp::Minus => "-",
+            UnaryOp::Not => "!",
+            UnaryOp::BitNot => "~",
+        };
+
+        write!(f, "{sign}")
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct If {
     pub condition: Box<dyn Expression>,
     pub consequence: Box<dyn Statement>,
     pub alternative: Option<Box<dyn Statement>>,
 }
 
-impl Into<Box<dyn Expression>> for If {
-    fn into(self) -> Box<dyn Expression> {
-        Box::new(self)
+impl From<If> for Box<dyn Expression> {
+    fn from(value: If) -> Self {
+        Box::new(value)
     }
 }
 
@@ -135,6 +226,10 @@ impl Expression for If {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
         visitor.visit_if(self);
     }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::If(self)
+    }
 }
 
 impl If {
@@ -150,3 +245,181 @@ impl If {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub callee: Box<dyn Expression>,
+    pub arguments: Vec<Box<dyn Expression>>,
+}
+
+impl From<Call> for Box<dyn Expression> {
+    fn from(value: Call) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Expression for Call {
+    fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
+        visitor.visit_call(self);
+    }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Call(self)
+    }
+}
+
+impl Call {
+    pub fn new(callee: Box<dyn Expression>, arguments: Vec<Box<dyn Expression>>) -> Self {
+        Self { callee, arguments }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub object: Box<dyn Expression>,
+    pub property: Box<dyn Expression>,
+}
+
+impl From<Member> for Box<dyn Expression> {
+    fn from(value: Member) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Expression for Member {
+    fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
+        visitor.visit_member(self);
+    }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Member(self)
+    }
+
+    fn as_member(&self) -> Option<&Member> {
+        Some(self)
+    }
+}
+
+impl Member {
+    pub fn new(object: Box<dyn Expression>, property: Box<dyn Expression>) -> Self {
+        Self { object, property }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Index {
+    pub object: Box<dyn Expression>,
+    pub index: Box<dyn Expression>,
+}
+
+impl From<Index> for Box<dyn Expression> {
+    fn from(value: Index) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Expression for Index {
+    fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
+        visitor.visit_index(self);
+    }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Index(self)
+    }
+}
+
+impl Index {
+    pub fn new(object: Box<dyn Expression>, index: Box<dyn Expression>) -> Self {
+        Self { object, index }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionLiteral {
+    pub parameters: Vec<Box<dyn Expression>>,
+    pub body: Rc<Box<dyn Statement>>,
+}
+
+impl From<FunctionLiteral> for Box<dyn Expression> {
+    fn from(value: FunctionLiteral) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Expression for FunctionLiteral {
+    fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
+        visitor.visit_function_literal(self);
+    }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::FunctionLiteral(self)
+    }
+}
+
+impl FunctionLiteral {
+    pub fn new(parameters: Vec<Box<dyn Expression>>, body: Rc<Box<dyn Statement>>) -> Self {
+        Self { parameters, body }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Range {
+    pub start: Box<dyn Expression>,
+    pub end: Box<dyn Expression>,
+    pub inclusive: bool,
+}
+
+impl From<Range> for Box<dyn Expression> {
+    fn from(value: Range) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Expression for Range {
+    fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
+        visitor.visit_range(self);
+    }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::Range(self)
+    }
+}
+
+impl Range {
+    pub fn new(start: Box<dyn Expression>, end: Box<dyn Expression>, inclusive: bool) -> Self {
+        Self { start, end, inclusive }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FStringPart {
+    Text(String),
+    Expression(Box<dyn Expression>),
+}
+
+#[derive(Debug, Clone)]
+pub struct FString {
+    pub parts: Vec<FStringPart>,
+}
+
+impl From<FString> for Box<dyn Expression> {
+    fn from(value: FString) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Expression for FString {
+    fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
+        visitor.visit_fstring(self);
+    }
+
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        ExpressionMut::FString(self)
+    }
+}
+
+impl FString {
+    pub fn new(parts: Vec<FStringPart>) -> Self {
+        Self { parts }
+    }
+}
@@ -1,6 +1,11 @@
 pub mod statement;
 pub mod expression;
 pub mod node;
+pub mod arena;
+pub mod visitor;
+
+#[cfg(test)]
+mod tests;
 
 use std::fmt;
 
@@ -12,12 +17,17 @@ use crate::{
 pub use statement::*;
 pub use expression::*;
 pub use node::*;
+pub use arena::{Arena, ArenaNode, ExprKind, NodeKind, StmtKind, TokenRef};
+pub use visitor::*;
 
-pub trait Expression: fmt::Debug {
+pub trait Expression: fmt::Debug + CloneExpression {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor);
-    fn token(&self) -> Option<&Token> { None }
+    fn kind_mut(&mut self) -> ExpressionMut<'_>;
+    fn token(&self) -> Option<&Token<'static>> { None }
     fn span(&self) -> Option<Span> { None }
     fn id(&self) -> Option<NodeId> { None }
+    fn as_identifier(&self) -> Option<&Identifier> { None }
+    fn as_member(&self) -> Option<&Member> { None }
 }
 
 pub trait ExpressionVisitor {
@@ -26,11 +36,18 @@ pub trait ExpressionVisitor {
     fn visit_binary(&mut self, binary: &Binary);
     fn visit_unary(&mut self, unary: &Unary);
     fn visit_if(&mut self, if_expr: &If);
+    fn visit_call(&mut self, call: &Call);
+    fn visit_member(&mut self, member: &Member);
+    fn visit_index(&mut self, index: &Index);
+    fn visit_function_literal(&mut self, function: &FunctionLiteral);
+    fn visit_range(&mut self, range: &Range);
+    fn visit_fstring(&mut self, fstring: &FString);
 }
 
-pub trait Statement: fmt::Debug {
+pub trait Statement: fmt::Debug + CloneStatement {
     fn accept(&self, visitor: &mut dyn StatementVisitor);
-    fn token(&self) -> Option<&Token> { None }
+    fn kind_mut(&mut self) -> StatementMut<'_>;
+    fn token(&self) -> Option<&Token<'static>> { None }
     fn span(&self) -> Option<Span> { None }
     fn id(&self) -> Option<NodeId> { None }
 }
@@ -38,12 +55,55 @@ pub trait Statement: fmt::Debug {
 pub trait StatementVisitor {
     fn visit_ast(&mut self, ast: &Ast);
     fn visit_let(&mut self, let_statement: &Let);
+    fn visit_const(&mut self, const_statement: &Const);
     fn visit_return(&mut self, return_statement: &Return);
     fn visit_expr(&mut self, expr: &Expr);
     fn visit_block(&mut self, block: &Block);
+    fn visit_function(&mut self, function: &Function);
+    fn visit_while(&mut self, while_statement: &While);
+    fn visit_for(&mut self, for_statement: &For);
+    fn visit_for_in(&mut self, for_in: &ForIn);
+    fn visit_break(&mut self, break_statement: &Break);
+    fn visit_continue(&mut self, continue_statement: &Continue);
+    fn visit_class(&mut self, class: &Class);
+    fn visit_error_statement(&mut self, error_statement: &ErrorStatement);
+}
+
+/// Copies a node behind a `dyn Expression`, implemented for every `Clone` node.
+pub trait CloneExpression {
+    fn clone_box(&self) -> Box<dyn Expression>;
+}
+
+impl<T: Expression + Clone + 'static> CloneExpression for T {
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Expression> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Copies a node behind a `dyn Statement`, implemented for every `Clone` node.
+pub trait CloneStatement {
+    fn clone_box(&self) -> Box<dyn Statement>;
 }
 
-#[derive(Debug, Default)]
+impl<T: Statement + Clone + 'static> CloneStatement for T {
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Statement> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Ast {
     statements: Vec<Box<dyn Statement>>,
 }
@@ -52,6 +112,10 @@ impl Statement for Ast {
     fn accept(&self, visitor: &mut dyn StatementVisitor) {
         visitor.visit_ast(self);
     }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Ast(self)
+    }
 }
 
 impl Ast {
@@ -67,4 +131,8 @@ impl Ast {
     pub fn statements(&self) -> &[Box<dyn Statement>] {
         &self.statements
     }
+
+    pub fn statements_mut(&mut self) -> &mut Vec<Box<dyn Statement>> {
+        &mut self.statements
+    }
 }
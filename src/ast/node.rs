@@ -2,59 +2,78 @@ use crate::{
     token::Token,
     span::Span,
 };
-use super::{Expression, Statement, ExpressionVisitor, StatementVisitor};
+use super::{Expression, Statement, ExpressionVisitor, StatementVisitor, ExpressionMut, StatementMut, Identifier, Member};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeId(pub u32);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node<T> {
     pub id: NodeId,
     pub kind: T,
-    pub token: Option<Token>
+    // First token of the node or, for nodes like calls, the one naming the operation.
+    pub token: Option<Token<'static>>,
+    // Everything from the first to the last token of the node.
+    pub span: Option<Span>,
 }
 
-impl<T: Expression + 'static> Into<Box<dyn Expression>> for Node<T> {
-    fn into(self) -> Box<dyn Expression> {
-        Box::new(self)
+impl<T: Expression + Clone + 'static> From<Node<T>> for Box<dyn Expression> {
+    fn from(value: Node<T>) -> Self {
+        Box::new(value)
     }
 }
 
-impl<T: Statement + 'static> Into<Box<dyn Statement>> for Node<T> {
-    fn into(self) -> Box<dyn Statement> {
-        Box::new(self)
+impl<T: Statement + Clone + 'static> From<Node<T>> for Box<dyn Statement> {
+    fn from(value: Node<T>) -> Self {
+        Box::new(value)
     }
 }
 
-impl<T: Expression> Expression for Node<T> {
+impl<T: Expression + Clone + 'static> Expression for Node<T> {
     fn accept(&self, visitor: &mut dyn ExpressionVisitor) {
         self.kind.accept(visitor);
     }
 
-    fn token(&self) -> Option<&Token> {
+    fn kind_mut(&mut self) -> ExpressionMut<'_> {
+        self.kind.kind_mut()
+    }
+
+    fn token(&self) -> Option<&Token<'static>> {
         self.token.as_ref()
     }
 
     fn span(&self) -> Option<Span> {
-        self.token.as_ref().map(|t| t.span).flatten()
+        self.span
     }
 
     fn id(&self) -> Option<NodeId> {
         Some(self.id)
     }
+
+    fn as_identifier(&self) -> Option<&Identifier> {
+        self.kind.as_identifier()
+    }
+
+    fn as_member(&self) -> Option<&Member> {
+        self.kind.as_member()
+    }
 }
 
-impl<T: Statement> Statement for Node<T> {
+impl<T: Statement + Clone + 'static> Statement for Node<T> {
     fn accept(&self, visitor: &mut dyn StatementVisitor) {
         self.kind.accept(visitor);
     }
 
-    fn token(&self) -> Option<&Token> {
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        self.kind.kind_mut()
+    }
+
+    fn token(&self) -> Option<&Token<'static>> {
         self.token.as_ref()
     }
 
     fn span(&self) -> Option<Span> {
-        self.token.as_ref().map(|t| t.span).flatten()
+        self.span
     }
 
     fn id(&self) -> Option<NodeId> {
@@ -63,11 +82,12 @@ impl<T: Statement> Statement for Node<T> {
 }
 
 impl<T> Node<T> {
-    pub fn new(id: NodeId, kind: T, token: Option<Token>) -> Self {
+    pub fn new(id: NodeId, kind: T, token: Option<Token<'static>>, span: Option<Span>) -> Self {
         Self {
             id,
             kind,
-            token
+            token,
+            span,
         }
     }
 }
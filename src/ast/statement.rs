@@ -1,14 +1,16 @@
+use std::rc::Rc;
+
 use super::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Let {
     pub identifier: Box<dyn Expression>,
     pub expression: Option<Box<dyn Expression>>,
 }
 
-impl Into<Box<dyn Statement>> for Let {
-    fn into(self) -> Box<dyn Statement> {
-        Box::new(self)
+impl From<Let> for Box<dyn Statement> {
+    fn from(value: Let) -> Self {
+        Box::new(value)
     }
 }
 
@@ -16,6 +18,10 @@ impl Statement for Let {
     fn accept(&self, visitor: &mut dyn StatementVisitor) {
         visitor.visit_let(self);
     }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Let(self)
+    }
 }
 
 impl Let {
@@ -24,14 +30,42 @@ impl Let {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct Const {
+    pub identifier: Box<dyn Expression>,
+    pub expression: Box<dyn Expression>,
+}
+
+impl From<Const> for Box<dyn Statement> {
+    fn from(value: Const) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for Const {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_const(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Const(self)
+    }
+}
+
+impl Const {
+    pub fn new(identifier: Box<dyn Expression>, expression: Box<dyn Expression>) -> Self {
+        Self { identifier, expression }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Return {
     pub expression: Option<Box<dyn Expression>>,
 }
 
-impl Into<Box<dyn Statement>> for Return {
-    fn into(self) -> Box<dyn Statement> {
-        Box::new(self)
+impl From<Return> for Box<dyn Statement> {
+    fn from(value: Return) -> Self {
+        Box::new(value)
     }
 }
 
@@ -39,6 +73,10 @@ impl Statement for Return {
     fn accept(&self, visitor: &mut dyn StatementVisitor) {
         visitor.visit_return(self)
     }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Return(self)
+    }
 }
 
 impl Return {
@@ -47,14 +85,14 @@ impl Return {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expr {
     pub expression: Box<dyn Expression>,
 }
 
-impl Into<Box<dyn Statement>> for Expr {
-    fn into(self) -> Box<dyn Statement> {
-        Box::new(self)
+impl From<Expr> for Box<dyn Statement> {
+    fn from(value: Expr) -> Self {
+        Box::new(value)
     }
 }
 
@@ -62,6 +100,10 @@ impl Statement for Expr {
     fn accept(&self, visitor: &mut dyn StatementVisitor) {
         visitor.visit_expr(self);
     }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Expr(self)
+    }
 }
 
 impl Expr {
@@ -70,14 +112,14 @@ impl Expr {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Block {
     pub statements: Vec<Box<dyn Statement>>,
 }
 
-impl Into<Box<dyn Statement>> for Block {
-    fn into(self) -> Box<dyn Statement> {
-        Box::new(self)
+impl From<Block> for Box<dyn Statement> {
+    fn from(value: Block) -> Self {
+        Box::new(value)
     }
 }
 
@@ -85,6 +127,10 @@ impl Statement for Block {
     fn accept(&self, visitor: &mut dyn StatementVisitor) {
         visitor.visit_block(self);
     }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Block(self)
+    }
 }
 
 impl Block {
@@ -92,3 +138,245 @@ impl Block {
         Self { statements }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: Box<dyn Expression>,
+    pub parameters: Vec<Box<dyn Expression>>,
+    pub body: Rc<Box<dyn Statement>>,
+}
+
+impl From<Function> for Box<dyn Statement> {
+    fn from(value: Function) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for Function {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_function(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Function(self)
+    }
+}
+
+impl Function {
+    pub fn new(name: Box<dyn Expression>, parameters: Vec<Box<dyn Expression>>, body: Rc<Box<dyn Statement>>) -> Self {
+        Self { name, parameters, body }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct While {
+    pub condition: Box<dyn Expression>,
+    pub body: Box<dyn Statement>,
+}
+
+impl From<While> for Box<dyn Statement> {
+    fn from(value: While) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for While {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_while(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::While(self)
+    }
+}
+
+impl While {
+    pub fn new(condition: Box<dyn Expression>, body: Box<dyn Statement>) -> Self {
+        Self { condition, body }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct For {
+    pub initializer: Option<Box<dyn Statement>>,
+    pub condition: Option<Box<dyn Expression>>,
+    pub update: Option<Box<dyn Expression>>,
+    pub body: Box<dyn Statement>,
+}
+
+impl From<For> for Box<dyn Statement> {
+    fn from(value: For) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for For {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_for(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::For(self)
+    }
+}
+
+impl For {
+    pub fn new(
+        initializer: Option<Box<dyn Statement>>,
+        condition: Option<Box<dyn Expression>>,
+        update: Option<Box<dyn Expression>>,
+        body: Box<dyn Statement>,
+    ) -> Self {
+        Self {
+            initializer,
+            condition,
+            update,
+            body,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ForIn {
+    pub variable: Box<dyn Expression>,
+    pub iterable: Box<dyn Expression>,
+    pub body: Box<dyn Statement>,
+}
+
+impl From<ForIn> for Box<dyn Statement> {
+    fn from(value: ForIn) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for ForIn {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_for_in(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::ForIn(self)
+    }
+}
+
+impl ForIn {
+    pub fn new(variable: Box<dyn Expression>, iterable: Box<dyn Expression>, body: Box<dyn Statement>) -> Self {
+        Self { variable, iterable, body }
+    }
+}
+
+// Placeholder a recovering parser leaves for a statement it could not parse.
+#[derive(Debug, Clone)]
+pub struct ErrorStatement;
+
+impl From<ErrorStatement> for Box<dyn Statement> {
+    fn from(value: ErrorStatement) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for ErrorStatement {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_error_statement(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::ErrorStatement(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Break;
+
+impl From<Break> for Box<dyn Statement> {
+    fn from(value: Break) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for Break {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_break(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Break(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Continue;
+
+impl From<Continue> for Box<dyn Statement> {
+    fn from(value: Continue) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for Continue {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_continue(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Continue(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: Box<dyn Expression>,
+    pub superclass: Option<Box<dyn Expression>>,
+    pub members: Vec<ClassMember>,
+}
+
+impl From<Class> for Box<dyn Statement> {
+    fn from(value: Class) -> Self {
+        Box::new(value)
+    }
+}
+
+impl Statement for Class {
+    fn accept(&self, visitor: &mut dyn StatementVisitor) {
+        visitor.visit_class(self);
+    }
+
+    fn kind_mut(&mut self) -> StatementMut<'_> {
+        StatementMut::Class(self)
+    }
+}
+
+impl Class {
+    pub fn new(name: Box<dyn Expression>, superclass: Option<Box<dyn Expression>>, members: Vec<ClassMember>) -> Self {
+        Self { name, superclass, members }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ClassMember {
+    Method(Method),
+    Property(Property),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodKind {
+    Constructor,
+    Method,
+    Getter,
+    Setter,
+}
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub name: Box<dyn Expression>,
+    pub kind: MethodKind,
+    pub is_static: bool,
+    pub parameters: Vec<Box<dyn Expression>>,
+    pub body: Rc<Box<dyn Statement>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: Box<dyn Expression>,
+    pub value: Option<Rc<Box<dyn Expression>>>,
+}
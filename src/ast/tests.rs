@@ -0,0 +1,226 @@
+use std::rc::Rc;
+
+use crate::{
+    lexer::Lexer,
+    parser::Parser,
+};
+
+use super::*;
+
+fn parse(input: &str) -> Ast {
+    Parser::new(Lexer::new(input.to_string())).parse().unwrap()
+}
+
+struct IdentifierCount;
+
+impl Visitor<usize> for IdentifierCount {
+    fn combine(&mut self, previous: usize, next: usize) -> usize {
+        previous + next
+    }
+
+    fn visit_identifier(&mut self, _identifier: &Identifier) -> usize {
+        1
+    }
+}
+
+// Names of every identifier, in source order.
+struct Names;
+
+impl Visitor<Vec<String>> for Names {
+    fn combine(&mut self, mut previous: Vec<String>, next: Vec<String>) -> Vec<String> {
+        previous.extend(next);
+        previous
+    }
+
+    fn visit_identifier(&mut self, identifier: &Identifier) -> Vec<String> {
+        vec![identifier.0.clone()]
+    }
+
+    fn visit_literal(&mut self, literal: &Literal) -> Vec<String> {
+        match literal {
+            Literal::Int(value) => vec![value.to_string()],
+            _ => vec![],
+        }
+    }
+}
+
+// Names of called functions, stopping at function bodies.
+struct Callees;
+
+impl Visitor<Vec<String>> for Callees {
+    fn combine(&mut self, mut previous: Vec<String>, next: Vec<String>) -> Vec<String> {
+        previous.extend(next);
+        previous
+    }
+
+    fn visit_call(&mut self, call: &Call) -> Vec<String> {
+        let mut callees: Vec<_> = call.callee.as_identifier().map(|identifier| identifier.0.clone()).into_iter().collect();
+        callees.extend(walk_call(self, call));
+        callees
+    }
+
+    fn visit_function_literal(&mut self, _function: &FunctionLiteral) -> Vec<String> {
+        vec![]
+    }
+}
+
+#[test]
+fn test_visitor() {
+    let test_cases = vec![
+        ("a + b * c;", 3),
+        ("fn f(x) { return x + y; }", 4),
+        ("class A extends B { x = c; m(p) { p } }", 7),
+        ("let s = f\"a${b}c${d.e}\";", 4),
+        ("for (let i in 0..n) { if (i) { break; } else { g(i); } }", 5),
+        ("1 + 2;", 0),
+    ];
+
+    for tc in test_cases {
+        let ast = parse(tc.0);
+
+        assert_eq!(tc.1, IdentifierCount.visit_ast(&ast), "{}", tc.0);
+        assert_eq!(tc.1, IdentifierCount.visit_statement(&ast), "{}", tc.0);
+    }
+
+    let expression = Parser::parse_expression("f(g(1), fn () { h() }, k.l())").unwrap();
+    assert_eq!(vec!["f", "g"], Callees.visit_expression(expression.as_ref()));
+}
+
+struct Rename<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+impl VisitorMut for Rename<'_> {
+    fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+        if identifier.0 == self.from {
+            identifier.0 = self.to.to_string();
+        }
+    }
+}
+
+// Replaces additions and multiplications of int literals with their result.
+struct FoldConstants;
+
+impl VisitorMut for FoldConstants {
+    fn visit_expression_mut(&mut self, expression: &mut Box<dyn Expression>) {
+        walk_expression_mut(self, expression.as_mut());
+
+        let ExpressionMut::Binary(binary) = expression.kind_mut() else {
+            return;
+        };
+
+        let value = match (binary.left.kind_mut(), binary.op, binary.right.kind_mut()) {
+            (ExpressionMut::Literal(Literal::Int(left)), BinaryOp::Plus, ExpressionMut::Literal(Literal::Int(right))) => *left + *right,
+            (ExpressionMut::Literal(Literal::Int(left)), BinaryOp::Mult, ExpressionMut::Literal(Literal::Int(right))) => *left * *right,
+            _ => return,
+        };
+
+        let id = expression.id().unwrap_or(NodeId(0));
+        let (token, span) = (expression.token().cloned(), expression.span());
+        *expression = Node::new(id, Literal::Int(value), token, span).into();
+    }
+}
+
+// Replaces `while (false)` loops with an empty block.
+struct DropDeadLoops;
+
+impl VisitorMut for DropDeadLoops {
+    fn visit_statement_mut(&mut self, statement: &mut Box<dyn Statement>) {
+        walk_statement_mut(self, statement.as_mut());
+
+        let StatementMut::While(while_statement) = statement.kind_mut() else {
+            return;
+        };
+
+        if let ExpressionMut::Literal(Literal::Bool(false)) = while_statement.condition.kind_mut() {
+            let id = statement.id().unwrap_or(NodeId(0));
+            let (token, span) = (statement.token().cloned(), statement.span());
+            *statement = Node::new(id, Block::new(vec![]), token, span).into();
+        }
+    }
+}
+
+#[test]
+fn test_visitor_mut() {
+    let mut ast = parse("fn f(x) { return x + g(x); } let y = (x) => x;");
+    Rename { from: "x", to: "z" }.visit_ast_mut(&mut ast);
+
+    assert_eq!(vec!["f", "z", "z", "g", "z", "y", "z", "z"], Names.visit_ast(&ast));
+
+    let test_cases = vec![
+        ("let a = 1 + 2 * 3;", vec!["a", "7"]),
+        ("let a = x + 2 * 3;", vec!["a", "x", "6"]),
+        ("f(1 + 1, x[2 * 2]);", vec!["f", "2", "x", "4"]),
+        ("while (1 + x) { 2 * 2; }", vec!["1", "x", "4"]),
+    ];
+
+    for tc in test_cases {
+        let mut ast = parse(tc.0);
+        FoldConstants.visit_ast_mut(&mut ast);
+
+        assert_eq!(tc.1, Names.visit_ast(&ast), "{}", tc.0);
+    }
+
+    // The folded node keeps the id and span of the expression it replaced.
+    let mut expression = Parser::parse_expression("2 * 3").unwrap();
+    let (id, span) = (expression.id(), expression.span());
+    FoldConstants.visit_expression_mut(&mut expression);
+
+    assert_eq!(id, expression.id());
+    assert_eq!(span, expression.span());
+    assert_eq!(vec!["6"], Names.visit_expression(expression.as_ref()));
+
+    let test_cases = vec![
+        ("while (false) { f(); } g();", vec!["g"]),
+        ("fn f() { while (false) { g(); } h(); }", vec!["f", "h"]),
+        ("class A { m() { while (false) { g(); } } }", vec!["A", "m"]),
+        ("while (x) { g(); }", vec!["x", "g"]),
+    ];
+
+    for tc in test_cases {
+        let mut ast = parse(tc.0);
+        DropDeadLoops.visit_ast_mut(&mut ast);
+
+        assert_eq!(tc.1, Names.visit_ast(&ast), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_visitor_mut_shared_nodes() {
+    let mut ast = parse("fn f(x) { return x; } class A { v = x + 1 * 2; }");
+    let StatementMut::Function(function) = ast.statements_mut()[0].kind_mut() else {
+        panic!("expected a function");
+    };
+    let body = function.body.clone();
+    let StatementMut::Class(class) = ast.statements_mut()[1].kind_mut() else {
+        panic!("expected a class");
+    };
+    let ClassMember::Property(property) = &class.members[0] else {
+        panic!("expected a property");
+    };
+    let value = property.value.clone().unwrap();
+
+    Rename { from: "x", to: "z" }.visit_ast_mut(&mut ast);
+    FoldConstants.visit_ast_mut(&mut ast);
+
+    // The tree is rewritten, even where its nodes are shared, while the holders
+    // of the shared nodes keep the originals.
+    assert_eq!(vec!["f", "z", "z", "A", "v", "z", "2"], Names.visit_ast(&ast));
+    assert_eq!(vec!["x"], Names.visit_statement(&**body));
+    assert_eq!(vec!["x", "1", "2"], Names.visit_expression(&**value));
+
+    // Nodes nothing else holds are rewritten in place.
+    let StatementMut::Function(function) = ast.statements_mut()[0].kind_mut() else {
+        panic!("expected a function");
+    };
+    let body = Rc::as_ptr(&function.body);
+
+    Rename { from: "z", to: "y" }.visit_ast_mut(&mut ast);
+
+    let StatementMut::Function(function) = ast.statements_mut()[0].kind_mut() else {
+        panic!("expected a function");
+    };
+    assert_eq!(body, Rc::as_ptr(&function.body));
+    assert_eq!(vec!["f", "y", "y", "A", "v", "y", "2"], Names.visit_ast(&ast));
+}
@@ -0,0 +1,764 @@
+use std::{mem, rc::Rc};
+
+use super::*;
+
+// Passes over the tree that only override the nodes they care about. The `walk_*`
+// functions do what the default `visit_*` methods do, recurse into the children,
+// so an override can still call them to carry on below its node.
+
+/// Visits the tree producing a value for every node.
+pub trait Visitor<O: Default> {
+    /// Merges the values of a node's children in the `walk_*` functions, starting
+    /// from `O::default()`. Keeps the last one unless overridden.
+    fn combine(&mut self, _previous: O, next: O) -> O {
+        next
+    }
+
+    fn visit_expression(&mut self, expression: &dyn Expression) -> O {
+        let mut dispatch = Dispatch { visitor: self, output: None };
+        expression.accept(&mut dispatch);
+        dispatch.output.unwrap_or_default()
+    }
+
+    fn visit_statement(&mut self, statement: &dyn Statement) -> O {
+        let mut dispatch = Dispatch { visitor: self, output: None };
+        statement.accept(&mut dispatch);
+        dispatch.output.unwrap_or_default()
+    }
+
+    fn visit_identifier(&mut self, _identifier: &Identifier) -> O {
+        O::default()
+    }
+
+    fn visit_literal(&mut self, _literal: &Literal) -> O {
+        O::default()
+    }
+
+    fn visit_binary(&mut self, binary: &Binary) -> O {
+        walk_binary(self, binary)
+    }
+
+    fn visit_unary(&mut self, unary: &Unary) -> O {
+        walk_unary(self, unary)
+    }
+
+    fn visit_if(&mut self, if_expr: &If) -> O {
+        walk_if(self, if_expr)
+    }
+
+    fn visit_call(&mut self, call: &Call) -> O {
+        walk_call(self, call)
+    }
+
+    fn visit_member(&mut self, member: &Member) -> O {
+        walk_member(self, member)
+    }
+
+    fn visit_index(&mut self, index: &Index) -> O {
+        walk_index(self, index)
+    }
+
+    fn visit_function_literal(&mut self, function: &FunctionLiteral) -> O {
+        walk_function_literal(self, function)
+    }
+
+    fn visit_range(&mut self, range: &Range) -> O {
+        walk_range(self, range)
+    }
+
+    fn visit_fstring(&mut self, fstring: &FString) -> O {
+        walk_fstring(self, fstring)
+    }
+
+    fn visit_ast(&mut self, ast: &Ast) -> O {
+        walk_ast(self, ast)
+    }
+
+    fn visit_let(&mut self, let_statement: &Let) -> O {
+        walk_let(self, let_statement)
+    }
+
+    fn visit_const(&mut self, const_statement: &Const) -> O {
+        walk_const(self, const_statement)
+    }
+
+    fn visit_return(&mut self, return_statement: &Return) -> O {
+        walk_return(self, return_statement)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> O {
+        walk_expr(self, expr)
+    }
+
+    fn visit_block(&mut self, block: &Block) -> O {
+        walk_block(self, block)
+    }
+
+    fn visit_function(&mut self, function: &Function) -> O {
+        walk_function(self, function)
+    }
+
+    fn visit_while(&mut self, while_statement: &While) -> O {
+        walk_while(self, while_statement)
+    }
+
+    fn visit_for(&mut self, for_statement: &For) -> O {
+        walk_for(self, for_statement)
+    }
+
+    fn visit_for_in(&mut self, for_in: &ForIn) -> O {
+        walk_for_in(self, for_in)
+    }
+
+    fn visit_break(&mut self, _break_statement: &Break) -> O {
+        O::default()
+    }
+
+    fn visit_continue(&mut self, _continue_statement: &Continue) -> O {
+        O::default()
+    }
+
+    fn visit_class(&mut self, class: &Class) -> O {
+        walk_class(self, class)
+    }
+
+    fn visit_error_statement(&mut self, _error_statement: &ErrorStatement) -> O {
+        O::default()
+    }
+}
+
+pub fn walk_binary<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, binary: &Binary) -> O {
+    Fold::new(visitor)
+        .expression(binary.left.as_ref())
+        .expression(binary.right.as_ref())
+        .finish()
+}
+
+pub fn walk_unary<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, unary: &Unary) -> O {
+    Fold::new(visitor)
+        .expression(unary.right.as_ref())
+        .finish()
+}
+
+pub fn walk_if<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, if_expr: &If) -> O {
+    Fold::new(visitor)
+        .expression(if_expr.condition.as_ref())
+        .statement(if_expr.consequence.as_ref())
+        .statements(&if_expr.alternative)
+        .finish()
+}
+
+pub fn walk_call<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, call: &Call) -> O {
+    Fold::new(visitor)
+        .expression(call.callee.as_ref())
+        .expressions(&call.arguments)
+        .finish()
+}
+
+pub fn walk_member<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, member: &Member) -> O {
+    Fold::new(visitor)
+        .expression(member.object.as_ref())
+        .expression(member.property.as_ref())
+        .finish()
+}
+
+pub fn walk_index<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, index: &Index) -> O {
+    Fold::new(visitor)
+        .expression(index.object.as_ref())
+        .expression(index.index.as_ref())
+        .finish()
+}
+
+pub fn walk_function_literal<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, function: &FunctionLiteral) -> O {
+    Fold::new(visitor)
+        .expressions(&function.parameters)
+        .statement(&**function.body)
+        .finish()
+}
+
+pub fn walk_range<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, range: &Range) -> O {
+    Fold::new(visitor)
+        .expression(range.start.as_ref())
+        .expression(range.end.as_ref())
+        .finish()
+}
+
+pub fn walk_fstring<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, fstring: &FString) -> O {
+    let expressions = fstring.parts.iter().filter_map(|part| match part {
+        FStringPart::Expression(expression) => Some(expression),
+        FStringPart::Text(_) => None,
+    });
+
+    Fold::new(visitor)
+        .expressions(expressions)
+        .finish()
+}
+
+pub fn walk_ast<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, ast: &Ast) -> O {
+    Fold::new(visitor)
+        .statements(ast.statements())
+        .finish()
+}
+
+pub fn walk_let<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, let_statement: &Let) -> O {
+    Fold::new(visitor)
+        .expression(let_statement.identifier.as_ref())
+        .expressions(&let_statement.expression)
+        .finish()
+}
+
+pub fn walk_const<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, const_statement: &Const) -> O {
+    Fold::new(visitor)
+        .expression(const_statement.identifier.as_ref())
+        .expression(const_statement.expression.as_ref())
+        .finish()
+}
+
+pub fn walk_return<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, return_statement: &Return) -> O {
+    Fold::new(visitor)
+        .expressions(&return_statement.expression)
+        .finish()
+}
+
+pub fn walk_expr<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, expr: &Expr) -> O {
+    Fold::new(visitor)
+        .expression(expr.expression.as_ref())
+        .finish()
+}
+
+pub fn walk_block<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, block: &Block) -> O {
+    Fold::new(visitor)
+        .statements(&block.statements)
+        .finish()
+}
+
+pub fn walk_function<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, function: &Function) -> O {
+    Fold::new(visitor)
+        .expression(function.name.as_ref())
+        .expressions(&function.parameters)
+        .statement(&**function.body)
+        .finish()
+}
+
+pub fn walk_while<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, while_statement: &While) -> O {
+    Fold::new(visitor)
+        .expression(while_statement.condition.as_ref())
+        .statement(while_statement.body.as_ref())
+        .finish()
+}
+
+pub fn walk_for<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, for_statement: &For) -> O {
+    Fold::new(visitor)
+        .statements(&for_statement.initializer)
+        .expressions(&for_statement.condition)
+        .expressions(&for_statement.update)
+        .statement(for_statement.body.as_ref())
+        .finish()
+}
+
+pub fn walk_for_in<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, for_in: &ForIn) -> O {
+    Fold::new(visitor)
+        .expression(for_in.variable.as_ref())
+        .expression(for_in.iterable.as_ref())
+        .statement(for_in.body.as_ref())
+        .finish()
+}
+
+pub fn walk_class<O: Default, V: Visitor<O> + ?Sized>(visitor: &mut V, class: &Class) -> O {
+    let fold = Fold::new(visitor)
+        .expression(class.name.as_ref())
+        .expressions(&class.superclass);
+
+    class.members.iter()
+        .fold(fold, |fold, member| match member {
+            ClassMember::Method(method) => fold
+                .expression(method.name.as_ref())
+                .expressions(&method.parameters)
+                .statement(&**method.body),
+            ClassMember::Property(property) => {
+                let fold = fold.expression(property.name.as_ref());
+
+                match property.value.as_deref() {
+                    Some(value) => fold.expression(value.as_ref()),
+                    None => fold,
+                }
+            }
+        })
+        .finish()
+}
+
+// Visits children one after the other, merging their values with `Visitor::combine`.
+struct Fold<'v, V: ?Sized, O> {
+    visitor: &'v mut V,
+    output: O,
+}
+
+impl<'v, V: Visitor<O> + ?Sized, O: Default> Fold<'v, V, O> {
+    fn new(visitor: &'v mut V) -> Self {
+        Self { visitor, output: O::default() }
+    }
+
+    fn expression(mut self, expression: &dyn Expression) -> Self {
+        let next = self.visitor.visit_expression(expression);
+        self.output = self.visitor.combine(mem::take(&mut self.output), next);
+        self
+    }
+
+    fn statement(mut self, statement: &dyn Statement) -> Self {
+        let next = self.visitor.visit_statement(statement);
+        self.output = self.visitor.combine(mem::take(&mut self.output), next);
+        self
+    }
+
+    fn expressions<'e>(self, expressions: impl IntoIterator<Item = &'e Box<dyn Expression>>) -> Self {
+        expressions.into_iter().fold(self, |fold, expression| fold.expression(expression.as_ref()))
+    }
+
+    fn statements<'s>(self, statements: impl IntoIterator<Item = &'s Box<dyn Statement>>) -> Self {
+        statements.into_iter().fold(self, |fold, statement| fold.statement(statement.as_ref()))
+    }
+
+    fn finish(self) -> O {
+        self.output
+    }
+}
+
+// Routes `accept` calls to the `Visitor` methods, keeping the value produced.
+struct Dispatch<'v, V: ?Sized, O> {
+    visitor: &'v mut V,
+    output: Option<O>,
+}
+
+impl<V: Visitor<O> + ?Sized, O: Default> ExpressionVisitor for Dispatch<'_, V, O> {
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        self.output = Some(self.visitor.visit_identifier(identifier));
+    }
+
+    fn visit_literal(&mut self, literal: &Literal) {
+        self.output = Some(self.visitor.visit_literal(literal));
+    }
+
+    fn visit_binary(&mut self, binary: &Binary) {
+        self.output = Some(self.visitor.visit_binary(binary));
+    }
+
+    fn visit_unary(&mut self, unary: &Unary) {
+        self.output = Some(self.visitor.visit_unary(unary));
+    }
+
+    fn visit_if(&mut self, if_expr: &If) {
+        self.output = Some(self.visitor.visit_if(if_expr));
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        self.output = Some(self.visitor.visit_call(call));
+    }
+
+    fn visit_member(&mut self, member: &Member) {
+        self.output = Some(self.visitor.visit_member(member));
+    }
+
+    fn visit_index(&mut self, index: &Index) {
+        self.output = Some(self.visitor.visit_index(index));
+    }
+
+    fn visit_function_literal(&mut self, function: &FunctionLiteral) {
+        self.output = Some(self.visitor.visit_function_literal(function));
+    }
+
+    fn visit_range(&mut self, range: &Range) {
+        self.output = Some(self.visitor.visit_range(range));
+    }
+
+    fn visit_fstring(&mut self, fstring: &FString) {
+        self.output = Some(self.visitor.visit_fstring(fstring));
+    }
+}
+
+impl<V: Visitor<O> + ?Sized, O: Default> StatementVisitor for Dispatch<'_, V, O> {
+    fn visit_ast(&mut self, ast: &Ast) {
+        self.output = Some(self.visitor.visit_ast(ast));
+    }
+
+    fn visit_let(&mut self, let_statement: &Let) {
+        self.output = Some(self.visitor.visit_let(let_statement));
+    }
+
+    fn visit_const(&mut self, const_statement: &Const) {
+        self.output = Some(self.visitor.visit_const(const_statement));
+    }
+
+    fn visit_return(&mut self, return_statement: &Return) {
+        self.output = Some(self.visitor.visit_return(return_statement));
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.output = Some(self.visitor.visit_expr(expr));
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.output = Some(self.visitor.visit_block(block));
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        self.output = Some(self.visitor.visit_function(function));
+    }
+
+    fn visit_while(&mut self, while_statement: &While) {
+        self.output = Some(self.visitor.visit_while(while_statement));
+    }
+
+    fn visit_for(&mut self, for_statement: &For) {
+        self.output = Some(self.visitor.visit_for(for_statement));
+    }
+
+    fn visit_for_in(&mut self, for_in: &ForIn) {
+        self.output = Some(self.visitor.visit_for_in(for_in));
+    }
+
+    fn visit_break(&mut self, break_statement: &Break) {
+        self.output = Some(self.visitor.visit_break(break_statement));
+    }
+
+    fn visit_continue(&mut self, continue_statement: &Continue) {
+        self.output = Some(self.visitor.visit_continue(continue_statement));
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.output = Some(self.visitor.visit_class(class));
+    }
+
+    fn visit_error_statement(&mut self, error_statement: &ErrorStatement) {
+        self.output = Some(self.visitor.visit_error_statement(error_statement));
+    }
+}
+
+/// The concrete node behind a `dyn Expression`, for rewriting it in place.
+pub enum ExpressionMut<'a> {
+    Identifier(&'a mut Identifier),
+    Literal(&'a mut Literal),
+    Binary(&'a mut Binary),
+    Unary(&'a mut Unary),
+    If(&'a mut If),
+    Call(&'a mut Call),
+    Member(&'a mut Member),
+    Index(&'a mut Index),
+    FunctionLiteral(&'a mut FunctionLiteral),
+    Range(&'a mut Range),
+    FString(&'a mut FString),
+}
+
+/// The concrete node behind a `dyn Statement`, for rewriting it in place.
+pub enum StatementMut<'a> {
+    Ast(&'a mut Ast),
+    Let(&'a mut Let),
+    Const(&'a mut Const),
+    Return(&'a mut Return),
+    Expr(&'a mut Expr),
+    Block(&'a mut Block),
+    Function(&'a mut Function),
+    While(&'a mut While),
+    For(&'a mut For),
+    ForIn(&'a mut ForIn),
+    Break(&'a mut Break),
+    Continue(&'a mut Continue),
+    Class(&'a mut Class),
+    ErrorStatement(&'a mut ErrorStatement),
+}
+
+/// Visits the tree mutably. Nodes come boxed so a pass can replace them with
+/// another kind of node. Function bodies and property initialisers sit behind an
+/// `Rc` that closures and classes may share. A shared one is copied before it is
+/// visited, leaving the values created from it untouched.
+pub trait VisitorMut {
+    fn visit_expression_mut(&mut self, expression: &mut Box<dyn Expression>) {
+        walk_expression_mut(self, expression.as_mut());
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Box<dyn Statement>) {
+        walk_statement_mut(self, statement.as_mut());
+    }
+
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+
+    fn visit_literal_mut(&mut self, _literal: &mut Literal) {}
+
+    fn visit_binary_mut(&mut self, binary: &mut Binary) {
+        walk_binary_mut(self, binary);
+    }
+
+    fn visit_unary_mut(&mut self, unary: &mut Unary) {
+        walk_unary_mut(self, unary);
+    }
+
+    fn visit_if_mut(&mut self, if_expr: &mut If) {
+        walk_if_mut(self, if_expr);
+    }
+
+    fn visit_call_mut(&mut self, call: &mut Call) {
+        walk_call_mut(self, call);
+    }
+
+    fn visit_member_mut(&mut self, member: &mut Member) {
+        walk_member_mut(self, member);
+    }
+
+    fn visit_index_mut(&mut self, index: &mut Index) {
+        walk_index_mut(self, index);
+    }
+
+    fn visit_function_literal_mut(&mut self, function: &mut FunctionLiteral) {
+        walk_function_literal_mut(self, function);
+    }
+
+    fn visit_range_mut(&mut self, range: &mut Range) {
+        walk_range_mut(self, range);
+    }
+
+    fn visit_fstring_mut(&mut self, fstring: &mut FString) {
+        walk_fstring_mut(self, fstring);
+    }
+
+    fn visit_ast_mut(&mut self, ast: &mut Ast) {
+        walk_ast_mut(self, ast);
+    }
+
+    fn visit_let_mut(&mut self, let_statement: &mut Let) {
+        walk_let_mut(self, let_statement);
+    }
+
+    fn visit_const_mut(&mut self, const_statement: &mut Const) {
+        walk_const_mut(self, const_statement);
+    }
+
+    fn visit_return_mut(&mut self, return_statement: &mut Return) {
+        walk_return_mut(self, return_statement);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_function_mut(&mut self, function: &mut Function) {
+        walk_function_mut(self, function);
+    }
+
+    fn visit_while_mut(&mut self, while_statement: &mut While) {
+        walk_while_mut(self, while_statement);
+    }
+
+    fn visit_for_mut(&mut self, for_statement: &mut For) {
+        walk_for_mut(self, for_statement);
+    }
+
+    fn visit_for_in_mut(&mut self, for_in: &mut ForIn) {
+        walk_for_in_mut(self, for_in);
+    }
+
+    fn visit_break_mut(&mut self, _break_statement: &mut Break) {}
+
+    fn visit_continue_mut(&mut self, _continue_statement: &mut Continue) {}
+
+    fn visit_class_mut(&mut self, class: &mut Class) {
+        walk_class_mut(self, class);
+    }
+
+    fn visit_error_statement_mut(&mut self, _error_statement: &mut ErrorStatement) {}
+}
+
+/// Calls the `visit_*_mut` method for the kind of `expression`.
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut dyn Expression) {
+    match expression.kind_mut() {
+        ExpressionMut::Identifier(identifier) => visitor.visit_identifier_mut(identifier),
+        ExpressionMut::Literal(literal) => visitor.visit_literal_mut(literal),
+        ExpressionMut::Binary(binary) => visitor.visit_binary_mut(binary),
+        ExpressionMut::Unary(unary) => visitor.visit_unary_mut(unary),
+        ExpressionMut::If(if_expr) => visitor.visit_if_mut(if_expr),
+        ExpressionMut::Call(call) => visitor.visit_call_mut(call),
+        ExpressionMut::Member(member) => visitor.visit_member_mut(member),
+        ExpressionMut::Index(index) => visitor.visit_index_mut(index),
+        ExpressionMut::FunctionLiteral(function) => visitor.visit_function_literal_mut(function),
+        ExpressionMut::Range(range) => visitor.visit_range_mut(range),
+        ExpressionMut::FString(fstring) => visitor.visit_fstring_mut(fstring),
+    }
+}
+
+/// Calls the `visit_*_mut` method for the kind of `statement`.
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut dyn Statement) {
+    match statement.kind_mut() {
+        StatementMut::Ast(ast) => visitor.visit_ast_mut(ast),
+        StatementMut::Let(let_statement) => visitor.visit_let_mut(let_statement),
+        StatementMut::Const(const_statement) => visitor.visit_const_mut(const_statement),
+        StatementMut::Return(return_statement) => visitor.visit_return_mut(return_statement),
+        StatementMut::Expr(expr) => visitor.visit_expr_mut(expr),
+        StatementMut::Block(block) => visitor.visit_block_mut(block),
+        StatementMut::Function(function) => visitor.visit_function_mut(function),
+        StatementMut::While(while_statement) => visitor.visit_while_mut(while_statement),
+        StatementMut::For(for_statement) => visitor.visit_for_mut(for_statement),
+        StatementMut::ForIn(for_in) => visitor.visit_for_in_mut(for_in),
+        StatementMut::Break(break_statement) => visitor.visit_break_mut(break_statement),
+        StatementMut::Continue(continue_statement) => visitor.visit_continue_mut(continue_statement),
+        StatementMut::Class(class) => visitor.visit_class_mut(class),
+        StatementMut::ErrorStatement(error_statement) => visitor.visit_error_statement_mut(error_statement),
+    }
+}
+
+pub fn walk_binary_mut<V: VisitorMut + ?Sized>(visitor: &mut V, binary: &mut Binary) {
+    visitor.visit_expression_mut(&mut binary.left);
+    visitor.visit_expression_mut(&mut binary.right);
+}
+
+pub fn walk_unary_mut<V: VisitorMut + ?Sized>(visitor: &mut V, unary: &mut Unary) {
+    visitor.visit_expression_mut(&mut unary.right);
+}
+
+pub fn walk_if_mut<V: VisitorMut + ?Sized>(visitor: &mut V, if_expr: &mut If) {
+    visitor.visit_expression_mut(&mut if_expr.condition);
+    visitor.visit_statement_mut(&mut if_expr.consequence);
+
+    if let Some(alternative) = &mut if_expr.alternative {
+        visitor.visit_statement_mut(alternative);
+    }
+}
+
+pub fn walk_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut Call) {
+    visitor.visit_expression_mut(&mut call.callee);
+
+    for argument in &mut call.arguments {
+        visitor.visit_expression_mut(argument);
+    }
+}
+
+pub fn walk_member_mut<V: VisitorMut + ?Sized>(visitor: &mut V, member: &mut Member) {
+    visitor.visit_expression_mut(&mut member.object);
+    visitor.visit_expression_mut(&mut member.property);
+}
+
+pub fn walk_index_mut<V: VisitorMut + ?Sized>(visitor: &mut V, index: &mut Index) {
+    visitor.visit_expression_mut(&mut index.object);
+    visitor.visit_expression_mut(&mut index.index);
+}
+
+pub fn walk_function_literal_mut<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut FunctionLiteral) {
+    walk_function_parts_mut(visitor, &mut function.parameters, &mut function.body);
+}
+
+pub fn walk_range_mut<V: VisitorMut + ?Sized>(visitor: &mut V, range: &mut Range) {
+    visitor.visit_expression_mut(&mut range.start);
+    visitor.visit_expression_mut(&mut range.end);
+}
+
+pub fn walk_fstring_mut<V: VisitorMut + ?Sized>(visitor: &mut V, fstring: &mut FString) {
+    for part in &mut fstring.parts {
+        if let FStringPart::Expression(expression) = part {
+            visitor.visit_expression_mut(expression);
+        }
+    }
+}
+
+pub fn walk_ast_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ast: &mut Ast) {
+    for statement in ast.statements_mut() {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_let_mut<V: VisitorMut + ?Sized>(visitor: &mut V, let_statement: &mut Let) {
+    visitor.visit_expression_mut(&mut let_statement.identifier);
+
+    if let Some(expression) = &mut let_statement.expression {
+        visitor.visit_expression_mut(expression);
+    }
+}
+
+pub fn walk_const_mut<V: VisitorMut + ?Sized>(visitor: &mut V, const_statement: &mut Const) {
+    visitor.visit_expression_mut(&mut const_statement.identifier);
+    visitor.visit_expression_mut(&mut const_statement.expression);
+}
+
+pub fn walk_return_mut<V: VisitorMut + ?Sized>(visitor: &mut V, return_statement: &mut Return) {
+    if let Some(expression) = &mut return_statement.expression {
+        visitor.visit_expression_mut(expression);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    visitor.visit_expression_mut(&mut expr.expression);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for statement in &mut block.statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_function_mut<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut Function) {
+    visitor.visit_expression_mut(&mut function.name);
+    walk_function_parts_mut(visitor, &mut function.parameters, &mut function.body);
+}
+
+pub fn walk_while_mut<V: VisitorMut + ?Sized>(visitor: &mut V, while_statement: &mut While) {
+    visitor.visit_expression_mut(&mut while_statement.condition);
+    visitor.visit_statement_mut(&mut while_statement.body);
+}
+
+pub fn walk_for_mut<V: VisitorMut + ?Sized>(visitor: &mut V, for_statement: &mut For) {
+    if let Some(initializer) = &mut for_statement.initializer {
+        visitor.visit_statement_mut(initializer);
+    }
+
+    if let Some(condition) = &mut for_statement.condition {
+        visitor.visit_expression_mut(condition);
+    }
+
+    if let Some(update) = &mut for_statement.update {
+        visitor.visit_expression_mut(update);
+    }
+
+    visitor.visit_statement_mut(&mut for_statement.body);
+}
+
+pub fn walk_for_in_mut<V: VisitorMut + ?Sized>(visitor: &mut V, for_in: &mut ForIn) {
+    visitor.visit_expression_mut(&mut for_in.variable);
+    visitor.visit_expression_mut(&mut for_in.iterable);
+    visitor.visit_statement_mut(&mut for_in.body);
+}
+
+pub fn walk_class_mut<V: VisitorMut + ?Sized>(visitor: &mut V, class: &mut Class) {
+    visitor.visit_expression_mut(&mut class.name);
+
+    if let Some(superclass) = &mut class.superclass {
+        visitor.visit_expression_mut(superclass);
+    }
+
+    for member in &mut class.members {
+        match member {
+            ClassMember::Method(method) => {
+                visitor.visit_expression_mut(&mut method.name);
+                walk_function_parts_mut(visitor, &mut method.parameters, &mut method.body);
+            }
+            ClassMember::Property(property) => {
+                visitor.visit_expression_mut(&mut property.name);
+
+                if let Some(value) = &mut property.value {
+                    visitor.visit_expression_mut(Rc::make_mut(value));
+                }
+            }
+        }
+    }
+}
+
+fn walk_function_parts_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    parameters: &mut [Box<dyn Expression>],
+    body: &mut Rc<Box<dyn Statement>>,
+) {
+    for parameter in parameters {
+        visitor.visit_expression_mut(parameter);
+    }
+
+    visitor.visit_statement_mut(Rc::make_mut(body));
+}
@@ -0,0 +1,50 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::value::Value;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    parent: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_parent(parent: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: Default::default(),
+            parent: Some(parent),
+        }
+    }
+
+    pub fn parent(&self) -> Option<Rc<RefCell<Environment>>> {
+        self.parent.clone()
+    }
+
+    pub fn define(&mut self, name: impl Into<String>, value: Value) {
+        self.values.insert(name.into(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref().and_then(|p| p.borrow().get(name)),
+        }
+    }
+
+    /// Assigns to the nearest enclosing binding, returns `false` if `name` is not defined.
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{error, fmt};
+
+use crate::{
+    ast::{BinaryOp, UnaryOp},
+    source_map::SourceMap,
+    span::*,
+};
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    UndefinedVariable(String),
+    InvalidAssignmentTarget,
+    BinaryOperands(BinaryOperandsError),
+    UnaryOperand(UnaryOperandError),
+    DivisionByZero,
+    IntegerOverflow,
+    InvalidShift(i64),
+    NotCallable(&'static str),
+    UndefinedProperty(UndefinedPropertyError),
+    InvalidIndex(InvalidIndexError),
+    IndexOutOfRange(i64),
+    ArgumentCount(ArgumentCountError),
+    StackOverflow,
+    RangeBounds(RangeBoundsError),
+    NotIterable(&'static str),
+    InvalidSuperclass(&'static str),
+    InvalidStatement,
+}
+
+#[derive(Debug, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, None)
+    }
+}
+
+impl Error {
+    pub(super) fn new(kind: ErrorKind, span: Option<Span>) -> Self {
+        Self { kind, span }
+    }
+
+    /// Same message as `Display`, with the position as `line:column` instead of a byte range.
+    pub fn display_with<'a>(&'a self, source_map: &'a SourceMap<'_>) -> impl fmt::Display + 'a {
+        fmt::from_fn(move |f| self.fmt_with(f, Some(source_map)))
+    }
+
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, source_map: Option<&SourceMap<'_>>) -> fmt::Result {
+        write!(f, "Runtime error")?;
+
+        match (self.span, source_map) {
+            (Some(span), Some(source_map)) => write!(f, " at {}", source_map.line_col(span.start))?,
+            (Some(span), None) => write!(f, " at {span}")?,
+            (None, _) => {}
+        }
+
+        write!(f, ": ")?;
+
+        match &self.kind {
+            ErrorKind::UndefinedVariable(name) => write!(f, "undefined variable '{name}'"),
+            ErrorKind::InvalidAssignmentTarget => write!(f, "invalid assignment target"),
+            ErrorKind::BinaryOperands(err) => write!(f, "{err}"),
+            ErrorKind::UnaryOperand(err) => write!(f, "{err}"),
+            ErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ErrorKind::IntegerOverflow => write!(f, "integer overflow"),
+            ErrorKind::InvalidShift(amount) => write!(f, "invalid shift amount {amount}"),
+            ErrorKind::NotCallable(type_name) => write!(f, "{type_name} is not callable"),
+            ErrorKind::UndefinedProperty(err) => write!(f, "{err}"),
+            ErrorKind::InvalidIndex(err) => write!(f, "{err}"),
+            ErrorKind::IndexOutOfRange(index) => write!(f, "index {index} out of range"),
+            ErrorKind::ArgumentCount(err) => write!(f, "{err}"),
+            ErrorKind::StackOverflow => write!(f, "stack overflow"),
+            ErrorKind::RangeBounds(err) => write!(f, "{err}"),
+            ErrorKind::NotIterable(type_name) => write!(f, "{type_name} is not iterable"),
+            ErrorKind::InvalidSuperclass(type_name) => write!(f, "superclass must be a class, got {type_name}"),
+            ErrorKind::InvalidStatement => write!(f, "statement failed to parse"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::BinaryOperands(err) => Some(err),
+            ErrorKind::UnaryOperand(err) => Some(err),
+            ErrorKind::UndefinedProperty(err) => Some(err),
+            ErrorKind::InvalidIndex(err) => Some(err),
+            ErrorKind::ArgumentCount(err) => Some(err),
+            ErrorKind::RangeBounds(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BinaryOperandsError {
+    pub op: BinaryOp,
+    pub left: &'static str,
+    pub right: &'static str,
+}
+
+impl fmt::Display for BinaryOperandsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported operand types for {}: {} and {}", self.op, self.left, self.right)
+    }
+}
+
+impl error::Error for BinaryOperandsError {}
+
+#[derive(Debug, Clone)]
+pub struct UnaryOperandError {
+    pub op: UnaryOp,
+    pub operand: &'static str,
+}
+
+impl fmt::Display for UnaryOperandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported operand type for {}: {}", self.op, self.operand)
+    }
+}
+
+impl error::Error for UnaryOperandError {}
+
+#[derive(Debug, Clone)]
+pub struct UndefinedPropertyError {
+    pub object: &'static str,
+    pub property: String,
+}
+
+impl fmt::Display for UndefinedPropertyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} has no property '{}'", self.object, self.property)
+    }
+}
+
+impl error::Error for UndefinedPropertyError {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidIndexError {
+    pub object: &'static str,
+    pub index: &'static str,
+}
+
+impl fmt::Display for InvalidIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} cannot be indexed by {}", self.object, self.index)
+    }
+}
+
+impl error::Error for InvalidIndexError {}
+
+#[derive(Debug, Clone)]
+pub struct ArgumentCountError {
+    pub expected: usize,
+    pub got: usize,
+}
+
+impl fmt::Display for ArgumentCountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {} arguments, got {}", self.expected, self.got)
+    }
+}
+
+impl error::Error for ArgumentCountError {}
+
+#[derive(Debug, Clone)]
+pub struct RangeBoundsError {
+    pub start: &'static str,
+    pub end: &'static str,
+}
+
+impl fmt::Display for RangeBoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "range bounds must be int, got {} and {}", self.start, self.end)
+    }
+}
+
+impl error::Error for RangeBoundsError {}
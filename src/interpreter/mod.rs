@@ -0,0 +1,737 @@
+pub mod value;
+pub mod environment;
+pub mod error;
+mod operators;
+
+#[cfg(test)]
+mod tests;
+
+use std::{cell::RefCell, mem, rc::Rc};
+
+use crate::{
+    ast::*,
+    span::Span,
+};
+
+pub use value::{Value, Closure, ClassObject, Instance};
+pub use environment::Environment;
+pub use error::Error;
+
+use error::{ErrorKind, UndefinedPropertyError, InvalidIndexError, ArgumentCountError, RangeBoundsError};
+
+const MAX_CALL_DEPTH: usize = 256;
+
+// Anything that stops normal evaluation and unwinds to an enclosing node.
+#[derive(Debug)]
+enum Interrupt {
+    Return(Value),
+    Break,
+    Continue,
+    Error(Error),
+}
+
+impl From<Error> for Interrupt {
+    fn from(value: Error) -> Self {
+        Interrupt::Error(value)
+    }
+}
+
+type Completion = Result<Value, Interrupt>;
+
+#[derive(Debug)]
+pub struct Interpreter {
+    environment: Rc<RefCell<Environment>>,
+    completion: Completion,
+    span: Option<Span>,
+    call_depth: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self {
+            environment: Default::default(),
+            completion: Ok(Value::Null),
+            span: None,
+            call_depth: 0,
+        }
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_environment(environment: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            environment,
+            ..Default::default()
+        }
+    }
+
+    pub fn environment(&self) -> Rc<RefCell<Environment>> {
+        self.environment.clone()
+    }
+
+    /// Runs every statement of `ast` and returns the value of the last one,
+    /// or the value of a top level `return`.
+    pub fn interpret(&mut self, ast: &Ast) -> Result<Value, Error> {
+        match self.execute(ast) {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            Err(Interrupt::Break | Interrupt::Continue) => Ok(Value::Null),
+            Err(Interrupt::Error(err)) => Err(err),
+        }
+    }
+
+    fn execute(&mut self, statement: &dyn Statement) -> Completion {
+        let span = mem::replace(&mut self.span, statement.span());
+        statement.accept(self);
+        self.span = span;
+
+        mem::replace(&mut self.completion, Ok(Value::Null))
+    }
+
+    fn evaluate(&mut self, expression: &dyn Expression) -> Completion {
+        let span = mem::replace(&mut self.span, expression.span());
+        expression.accept(self);
+        self.span = span;
+
+        mem::replace(&mut self.completion, Ok(Value::Null))
+    }
+
+    fn execute_statements(&mut self, statements: &[Box<dyn Statement>]) -> Completion {
+        let mut value = Value::Null;
+
+        for statement in statements {
+            value = self.execute(statement.as_ref())?;
+        }
+
+        Ok(value)
+    }
+
+    fn execute_in_scope(&mut self, statements: &[Box<dyn Statement>]) -> Completion {
+        let scope = Environment::with_parent(self.environment.clone());
+        let parent = mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+        let result = self.execute_statements(statements);
+        self.environment = parent;
+
+        result
+    }
+
+    fn eval_let(&mut self, let_statement: &Let) -> Completion {
+        let name = self.identifier_name(let_statement.identifier.as_ref())?;
+        let value = match &let_statement.expression {
+            Some(expression) => self.evaluate(expression.as_ref())?,
+            None => Value::Null,
+        };
+
+        self.environment.borrow_mut().define(name, value);
+
+        Ok(Value::Null)
+    }
+
+    fn eval_const(&mut self, const_statement: &Const) -> Completion {
+        let name = self.identifier_name(const_statement.identifier.as_ref())?;
+        let value = self.evaluate(const_statement.expression.as_ref())?;
+
+        self.environment.borrow_mut().define(name, value);
+
+        Ok(Value::Null)
+    }
+
+    fn eval_return(&mut self, return_statement: &Return) -> Completion {
+        let value = match &return_statement.expression {
+            Some(expression) => self.evaluate(expression.as_ref())?,
+            None => Value::Null,
+        };
+
+        Err(Interrupt::Return(value))
+    }
+
+    fn eval_while(&mut self, while_statement: &While) -> Completion {
+        while self.evaluate(while_statement.condition.as_ref())?.is_truthy() {
+            match self.execute(while_statement.body.as_ref()) {
+                Ok(_) | Err(Interrupt::Continue) => {},
+                Err(Interrupt::Break) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn eval_for(&mut self, for_statement: &For) -> Completion {
+        let scope = Environment::with_parent(self.environment.clone());
+        let parent = mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+        let result = self.eval_for_in_scope(for_statement);
+        self.environment = parent;
+
+        result
+    }
+
+    fn eval_for_in_scope(&mut self, for_statement: &For) -> Completion {
+        if let Some(initializer) = &for_statement.initializer {
+            self.execute(initializer.as_ref())?;
+        }
+
+        loop {
+            if let Some(condition) = &for_statement.condition
+                && !self.evaluate(condition.as_ref())?.is_truthy()
+            {
+                break;
+            }
+
+            match self.execute(for_statement.body.as_ref()) {
+                Ok(_) | Err(Interrupt::Continue) => {},
+                Err(Interrupt::Break) => break,
+                Err(err) => return Err(err),
+            }
+
+            if let Some(update) = &for_statement.update {
+                self.evaluate(update.as_ref())?;
+            }
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn eval_for_in(&mut self, for_in: &ForIn) -> Completion {
+        let name = self.identifier_name(for_in.variable.as_ref())?;
+        let iterable = self.evaluate(for_in.iterable.as_ref())?;
+
+        let items: Box<dyn Iterator<Item = Value>> = match iterable {
+            Value::Range { start, end, inclusive: false } => Box::new((start..end).map(Value::Int)),
+            Value::Range { start, end, inclusive: true } => Box::new((start..=end).map(Value::Int)),
+            Value::Str(value) => Box::new(
+                value.chars()
+                    .map(|ch| Value::Str(ch.to_string()))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            ),
+            value => return Err(Error::new(
+                ErrorKind::NotIterable(value.type_name()),
+                for_in.iterable.span(),
+            ).into()),
+        };
+
+        for item in items {
+            let mut scope = Environment::with_parent(self.environment.clone());
+            scope.define(name.as_str(), item);
+
+            let parent = mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+            let result = self.execute(for_in.body.as_ref());
+            self.environment = parent;
+
+            match result {
+                Ok(_) | Err(Interrupt::Continue) => {},
+                Err(Interrupt::Break) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Value::Null)
+    }
+
+    fn eval_range(&mut self, range: &Range) -> Completion {
+        let start = self.evaluate(range.start.as_ref())?;
+        let end = self.evaluate(range.end.as_ref())?;
+
+        match (&start, &end) {
+            (&Value::Int(start), &Value::Int(end)) => Ok(Value::Range { start, end, inclusive: range.inclusive }),
+            _ => Err(self.make_error(ErrorKind::RangeBounds(RangeBoundsError {
+                start: start.type_name(),
+                end: end.type_name(),
+            })).into()),
+        }
+    }
+
+    fn eval_fstring(&mut self, fstring: &FString) -> Completion {
+        let mut result = String::new();
+
+        for part in &fstring.parts {
+            match part {
+                FStringPart::Text(text) => result.push_str(text),
+                FStringPart::Expression(expression) => {
+                    let value = self.evaluate(expression.as_ref())?;
+                    result.push_str(&value.to_string());
+                }
+            }
+        }
+
+        Ok(Value::Str(result))
+    }
+
+    fn eval_identifier(&mut self, identifier: &Identifier) -> Completion {
+        self.environment
+            .borrow()
+            .get(&identifier.0)
+            .ok_or_else(|| self.make_error(ErrorKind::UndefinedVariable(identifier.0.clone())).into())
+    }
+
+    fn eval_binary(&mut self, binary: &Binary) -> Completion {
+        match binary.op {
+            BinaryOp::Assign => {
+                let value = self.evaluate(binary.right.as_ref())?;
+                self.assign(binary.left.as_ref(), value)
+            }
+            BinaryOp::And => {
+                let left = self.evaluate(binary.left.as_ref())?;
+
+                if !left.is_truthy() {
+                    return Ok(Value::Bool(false));
+                }
+
+                let right = self.evaluate(binary.right.as_ref())?;
+                Ok(Value::Bool(right.is_truthy()))
+            }
+            BinaryOp::Or => {
+                let left = self.evaluate(binary.left.as_ref())?;
+
+                if left.is_truthy() {
+                    return Ok(Value::Bool(true));
+                }
+
+                let right = self.evaluate(binary.right.as_ref())?;
+                Ok(Value::Bool(right.is_truthy()))
+            }
+            op => {
+                // The object of a member target is evaluated once, for both the read and the write.
+                if operators::compound_assignment_op(op).is_some()
+                    && let Some(member) = binary.left.as_member()
+                {
+                    let object = self.evaluate(member.object.as_ref())?;
+                    let property = self.identifier_name(member.property.as_ref())?;
+                    let left = self.get_property(object.clone(), property.clone())?;
+                    let right = self.evaluate(binary.right.as_ref())?;
+                    let value = operators::binary(op, left, right)
+                        .map_err(|kind| self.make_error(kind))?;
+
+                    return self.set_property(object, property, value);
+                }
+
+                let left = self.evaluate(binary.left.as_ref())?;
+                let right = self.evaluate(binary.right.as_ref())?;
+                let value = operators::binary(op, left, right)
+                    .map_err(|kind| self.make_error(kind))?;
+
+                if operators::compound_assignment_op(op).is_some() {
+                    self.assign(binary.left.as_ref(), value)
+                } else {
+                    Ok(value)
+                }
+            }
+        }
+    }
+
+    fn eval_unary(&mut self, unary: &Unary) -> Completion {
+        let operand = self.evaluate(unary.right.as_ref())?;
+
+        operators::unary(unary.op, operand)
+            .map_err(|kind| self.make_error(kind).into())
+    }
+
+    fn eval_if(&mut self, if_expr: &If) -> Completion {
+        let condition = self.evaluate(if_expr.condition.as_ref())?;
+
+        if condition.is_truthy() {
+            self.execute(if_expr.consequence.as_ref())
+        } else if let Some(alternative) = &if_expr.alternative {
+            self.execute(alternative.as_ref())
+        } else {
+            Ok(Value::Null)
+        }
+    }
+
+    fn eval_call(&mut self, call: &Call) -> Completion {
+        let callee = self.evaluate(call.callee.as_ref())?;
+        let mut arguments = Vec::with_capacity(call.arguments.len());
+
+        for argument in &call.arguments {
+            arguments.push(self.evaluate(argument.as_ref())?);
+        }
+
+        match callee {
+            Value::Function(closure) => self.call_closure(&closure, arguments),
+            Value::Class(class) => self.instantiate(class, arguments),
+            callee => Err(self.make_error(ErrorKind::NotCallable(callee.type_name())).into()),
+        }
+    }
+
+    fn call_closure(&mut self, closure: &Closure, arguments: Vec<Value>) -> Completion {
+        if closure.parameters.len() != arguments.len() {
+            return Err(self.make_error(ErrorKind::ArgumentCount(ArgumentCountError {
+                expected: closure.parameters.len(),
+                got: arguments.len(),
+            })).into());
+        }
+
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(self.make_error(ErrorKind::StackOverflow).into());
+        }
+
+        let mut scope = Environment::with_parent(closure.environment.clone());
+
+        for (name, value) in closure.parameters.iter().zip(arguments) {
+            scope.define(name.as_str(), value);
+        }
+
+        let caller = mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+        self.call_depth += 1;
+        let result = self.execute(&**closure.body);
+        self.call_depth -= 1;
+        self.environment = caller;
+
+        match result {
+            Ok(_) | Err(Interrupt::Break | Interrupt::Continue) => Ok(Value::Null),
+            Err(Interrupt::Return(value)) => Ok(value),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn instantiate(&mut self, class: Rc<ClassObject>, arguments: Vec<Value>) -> Completion {
+        let constructor = class.find(|class| class.constructor.as_ref());
+        let instance = Value::Instance(Rc::new(RefCell::new(Instance::new(class.clone()))));
+        self.initialize_fields(&class, &instance)?;
+
+        match constructor {
+            Some(constructor) => {
+                self.call_closure(&constructor.bind(instance.clone()), arguments)?;
+            }
+            None if !arguments.is_empty() => {
+                return Err(self.make_error(ErrorKind::ArgumentCount(ArgumentCountError {
+                    expected: 0,
+                    got: arguments.len(),
+                })).into());
+            }
+            None => {}
+        }
+
+        Ok(instance)
+    }
+
+    /// Evaluates the property initialisers of `class` for a new instance, superclass
+    /// ones first, in the class' environment with `this` bound to the instance.
+    fn initialize_fields(&mut self, class: &ClassObject, instance: &Value) -> Result<(), Interrupt> {
+        if let Some(superclass) = &class.superclass {
+            self.initialize_fields(superclass, instance)?;
+        }
+
+        let Value::Instance(object) = instance else {
+            return Ok(());
+        };
+
+        let mut scope = Environment::with_parent(class.environment.clone());
+        scope.define("this", instance.clone());
+
+        let caller = mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+        let result = class.fields.iter().try_for_each(|(name, value)| {
+            let value = match value.as_deref() {
+                Some(value) => self.evaluate(value.as_ref())?,
+                None => Value::Null,
+            };
+
+            object.borrow_mut().fields.insert(name.clone(), value);
+            Ok(())
+        });
+        self.environment = caller;
+
+        result
+    }
+
+    fn make_closure(
+        &self,
+        name: Option<String>,
+        parameters: &[Box<dyn Expression>],
+        body: &Rc<Box<dyn Statement>>,
+    ) -> Result<Rc<Closure>, Error> {
+        let parameters = parameters
+            .iter()
+            .map(|parameter| self.identifier_name(parameter.as_ref()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Rc::new(Closure {
+            name,
+            parameters,
+            body: body.clone(),
+            environment: self.environment.clone(),
+        }))
+    }
+
+    fn eval_function(&mut self, function: &Function) -> Completion {
+        let name = self.identifier_name(function.name.as_ref())?;
+        let closure = self.make_closure(Some(name.clone()), &function.parameters, &function.body)?;
+
+        self.environment.borrow_mut().define(name, Value::Function(closure));
+
+        Ok(Value::Null)
+    }
+
+    /// Property initialisers are kept unevaluated and run for every new instance.
+    fn eval_class(&mut self, class: &Class) -> Completion {
+        let name = self.identifier_name(class.name.as_ref())?;
+        let superclass = match &class.superclass {
+            Some(superclass) => match self.evaluate(superclass.as_ref())? {
+                Value::Class(superclass) => Some(superclass),
+                value => return Err(Error::new(
+                    ErrorKind::InvalidSuperclass(value.type_name()),
+                    superclass.span(),
+                ).into()),
+            },
+            None => None,
+        };
+        let mut object = ClassObject {
+            name: name.clone(),
+            superclass,
+            constructor: None,
+            members: Default::default(),
+            static_members: Default::default(),
+            fields: vec![],
+            environment: self.environment.clone(),
+        };
+
+        for member in &class.members {
+            match member {
+                ClassMember::Method(method) => {
+                    let method_name = self.identifier_name(method.name.as_ref())?;
+                    let closure = self.make_closure(Some(method_name.clone()), &method.parameters, &method.body)?;
+                    let members = if method.is_static { &mut object.static_members } else { &mut object.members };
+
+                    match method.kind {
+                        MethodKind::Constructor => object.constructor = Some(closure),
+                        MethodKind::Method => { members.methods.insert(method_name, closure); }
+                        MethodKind::Getter => { members.getters.insert(method_name, closure); }
+                        MethodKind::Setter => { members.setters.insert(method_name, closure); }
+                    }
+                }
+                ClassMember::Property(property) => {
+                    let property_name = self.identifier_name(property.name.as_ref())?;
+                    object.fields.push((property_name, property.value.clone()));
+                }
+            }
+        }
+
+        self.environment.borrow_mut().define(name, Value::Class(Rc::new(object)));
+
+        Ok(Value::Null)
+    }
+
+    fn eval_member(&mut self, member: &Member) -> Completion {
+        let object = self.evaluate(member.object.as_ref())?;
+        let property = self.identifier_name(member.property.as_ref())?;
+
+        self.get_property(object, property)
+    }
+
+    fn get_property(&mut self, object: Value, property: String) -> Completion {
+        if let Value::Instance(instance) = &object
+            && let Some(value) = instance.borrow().fields.get(&property)
+        {
+            return Ok(value.clone());
+        }
+
+        if let Some((class, is_static)) = class_of(&object) {
+            if let Some(getter) = class.find(|class| class.members(is_static).getters.get(&property)) {
+                return self.call_closure(&getter.bind(object), vec![]);
+            }
+
+            if let Some(method) = class.find(|class| class.members(is_static).methods.get(&property)) {
+                return Ok(Value::Function(Rc::new(method.bind(object))));
+            }
+        }
+
+        Err(self.make_error(ErrorKind::UndefinedProperty(UndefinedPropertyError {
+            object: object.type_name(),
+            property,
+        })).into())
+    }
+
+    fn assign_member(&mut self, member: &Member, value: Value) -> Completion {
+        let object = self.evaluate(member.object.as_ref())?;
+        let property = self.identifier_name(member.property.as_ref())?;
+
+        self.set_property(object, property, value)
+    }
+
+    fn set_property(&mut self, object: Value, property: String, value: Value) -> Completion {
+        if let Some((class, is_static)) = class_of(&object)
+            && let Some(setter) = class.find(|class| class.members(is_static).setters.get(&property))
+        {
+            self.call_closure(&setter.bind(object), vec![value.clone()])?;
+            return Ok(value);
+        }
+
+        match &object {
+            Value::Instance(instance) => {
+                instance.borrow_mut().fields.insert(property, value.clone());
+                Ok(value)
+            }
+            _ => Err(self.make_error(ErrorKind::UndefinedProperty(UndefinedPropertyError {
+                object: object.type_name(),
+                property,
+            })).into()),
+        }
+    }
+
+    fn eval_index(&mut self, index: &Index) -> Completion {
+        let object = self.evaluate(index.object.as_ref())?;
+        let position = self.evaluate(index.index.as_ref())?;
+
+        match (&object, &position) {
+            (Value::Str(value), Value::Int(i)) => usize::try_from(*i).ok()
+                .and_then(|i| value.chars().nth(i))
+                .map(|ch| Value::Str(ch.to_string()))
+                .ok_or_else(|| self.make_error(ErrorKind::IndexOutOfRange(*i)).into()),
+            _ => Err(self.make_error(ErrorKind::InvalidIndex(InvalidIndexError {
+                object: object.type_name(),
+                index: position.type_name(),
+            })).into()),
+        }
+    }
+
+    fn assign(&mut self, target: &dyn Expression, value: Value) -> Completion {
+        if let Some(member) = target.as_member() {
+            return self.assign_member(member, value);
+        }
+
+        let name = self.identifier_name(target)?;
+
+        if self.environment.borrow_mut().assign(&name, value.clone()) {
+            Ok(value)
+        } else {
+            Err(Error::new(ErrorKind::UndefinedVariable(name), target.span()).into())
+        }
+    }
+
+    fn identifier_name(&self, expression: &dyn Expression) -> Result<String, Error> {
+        match expression.as_identifier() {
+            Some(identifier) => Ok(identifier.0.clone()),
+            None => Err(Error::new(ErrorKind::InvalidAssignmentTarget, expression.span())),
+        }
+    }
+
+    fn make_error(&self, kind: ErrorKind) -> Error {
+        Error::new(kind, self.span)
+    }
+}
+
+// The class whose members `value` can reach, and whether those are its static members.
+fn class_of(value: &Value) -> Option<(Rc<ClassObject>, bool)> {
+    match value {
+        Value::Instance(instance) => Some((instance.borrow().class.clone(), false)),
+        Value::Class(class) => Some((class.clone(), true)),
+        _ => None,
+    }
+}
+
+impl StatementVisitor for Interpreter {
+    fn visit_ast(&mut self, ast: &Ast) {
+        self.completion = self.execute_statements(ast.statements());
+    }
+
+    fn visit_let(&mut self, let_statement: &Let) {
+        self.completion = self.eval_let(let_statement);
+    }
+
+    fn visit_const(&mut self, const_statement: &Const) {
+        self.completion = self.eval_const(const_statement);
+    }
+
+    fn visit_return(&mut self, return_statement: &Return) {
+        self.completion = self.eval_return(return_statement);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.completion = self.evaluate(expr.expression.as_ref());
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.completion = self.execute_in_scope(&block.statements);
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        self.completion = self.eval_function(function);
+    }
+
+    fn visit_while(&mut self, while_statement: &While) {
+        self.completion = self.eval_while(while_statement);
+    }
+
+    fn visit_for(&mut self, for_statement: &For) {
+        self.completion = self.eval_for(for_statement);
+    }
+
+    fn visit_for_in(&mut self, for_in: &ForIn) {
+        self.completion = self.eval_for_in(for_in);
+    }
+
+    fn visit_break(&mut self, _break_statement: &Break) {
+        self.completion = Err(Interrupt::Break);
+    }
+
+    fn visit_continue(&mut self, _continue_statement: &Continue) {
+        self.completion = Err(Interrupt::Continue);
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        self.completion = self.eval_class(class);
+    }
+
+    fn visit_error_statement(&mut self, _error_statement: &ErrorStatement) {
+        self.completion = Err(self.make_error(ErrorKind::InvalidStatement).into());
+    }
+}
+
+impl ExpressionVisitor for Interpreter {
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        self.completion = self.eval_identifier(identifier);
+    }
+
+    fn visit_literal(&mut self, literal: &Literal) {
+        self.completion = Ok(match literal {
+            Literal::Int(value) => Value::Int(*value),
+            Literal::Str(value) => Value::Str(value.clone()),
+            Literal::Bool(value) => Value::Bool(*value),
+            Literal::Float(value) => Value::Float(*value),
+            Literal::Null => Value::Null,
+        });
+    }
+
+    fn visit_binary(&mut self, binary: &Binary) {
+        self.completion = self.eval_binary(binary);
+    }
+
+    fn visit_unary(&mut self, unary: &Unary) {
+        self.completion = self.eval_unary(unary);
+    }
+
+    fn visit_if(&mut self, if_expr: &If) {
+        self.completion = self.eval_if(if_expr);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        self.completion = self.eval_call(call);
+    }
+
+    fn visit_member(&mut self, member: &Member) {
+        self.completion = self.eval_member(member);
+    }
+
+    fn visit_index(&mut self, index: &Index) {
+        self.completion = self.eval_index(index);
+    }
+
+    fn visit_function_literal(&mut self, function: &FunctionLiteral) {
+        self.completion = self
+            .make_closure(None, &function.parameters, &function.body)
+            .map(Value::Function)
+            .map_err(Interrupt::from);
+    }
+
+    fn visit_range(&mut self, range: &Range) {
+        self.completion = self.eval_range(range);
+    }
+
+    fn visit_fstring(&mut self, fstring: &FString) {
+        self.completion = self.eval_fstring(fstring);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::ast::{BinaryOp, UnaryOp};
+
+use super::{error::*, value::Value};
+
+pub(super) fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, ErrorKind> {
+    use BinaryOp::*;
+
+    match op {
+        Plus => plus(left, right),
+        Minus => arithmetic(op, left, right, i64::checked_sub, |l, r| l - r),
+        Mult => arithmetic(op, left, right, i64::checked_mul, |l, r| l * r),
+        Div => {
+            if let (Value::Int(_), Value::Int(0)) = (&left, &right) {
+                return Err(ErrorKind::DivisionByZero);
+            }
+
+            arithmetic(op, left, right, i64::checked_div, |l, r| l / r)
+        }
+        Mod => {
+            if let (Value::Int(_), Value::Int(0)) = (&left, &right) {
+                return Err(ErrorKind::DivisionByZero);
+            }
+
+            arithmetic(op, left, right, i64::checked_rem, |l, r| l % r)
+        }
+        Eq => Ok(Value::Bool(equals(&left, &right))),
+        Neq => Ok(Value::Bool(!equals(&left, &right))),
+        Gt | Gte | Lt | Lte => compare(op, left, right),
+        And => Ok(Value::Bool(left.is_truthy() && right.is_truthy())),
+        Or => Ok(Value::Bool(left.is_truthy() || right.is_truthy())),
+        BitAnd => integer(op, left, right, |l, r| Ok(l & r)),
+        BitOr => integer(op, left, right, |l, r| Ok(l | r)),
+        BitXor => integer(op, left, right, |l, r| Ok(l ^ r)),
+        ShiftLeft => integer(op, left, right, |l, r| Ok(l << shift_amount(r)?)),
+        ShiftRight => integer(op, left, right, |l, r| Ok(l >> shift_amount(r)?)),
+        // Shifts in zeros, treating the operand as its two's complement bits.
+        UnsignedShiftRight => integer(op, left, right, |l, r| Ok(((l as u64) >> shift_amount(r)?) as i64)),
+        _ => match compound_assignment_op(op) {
+            Some(op) => binary(op, left, right),
+            None => Err(operands_error(op, &left, &right)),
+        },
+    }
+}
+
+pub(super) fn unary(op: UnaryOp, operand: Value) -> Result<Value, ErrorKind> {
+    match (op, operand) {
+        (UnaryOp::Minus, Value::Int(value)) => value.checked_neg()
+            .map(Value::Int)
+            .ok_or(ErrorKind::IntegerOverflow),
+        (UnaryOp::Minus, Value::Float(value)) => Ok(Value::Float(-value)),
+        (UnaryOp::Not, value) => Ok(Value::Bool(!value.is_truthy())),
+        (UnaryOp::BitNot, Value::Int(value)) => Ok(Value::Int(!value)),
+        (op, operand) => Err(ErrorKind::UnaryOperand(UnaryOperandError {
+            op,
+            operand: operand.type_name(),
+        })),
+    }
+}
+
+/// Maps a compound assignment such as `+=` to the operator it applies.
+pub(super) fn compound_assignment_op(op: BinaryOp) -> Option<BinaryOp> {
+    use BinaryOp::*;
+
+    match op {
+        AssignPlus => Some(Plus),
+        AssignMinus => Some(Minus),
+        AssignMult => Some(Mult),
+        AssignDiv => Some(Div),
+        AssignMod => Some(Mod),
+        AssignBitAnd => Some(BitAnd),
+        AssignBitOr => Some(BitOr),
+        AssignBitXor => Some(BitXor),
+        AssignShiftLeft => Some(ShiftLeft),
+        AssignShiftRight => Some(ShiftRight),
+        AssignUnsignedShiftRight => Some(UnsignedShiftRight),
+        _ => None,
+    }
+}
+
+fn plus(left: Value, right: Value) -> Result<Value, ErrorKind> {
+    match (left, right) {
+        (Value::Str(left), right) => Ok(Value::Str(format!("{left}{right}"))),
+        (left, Value::Str(right)) => Ok(Value::Str(format!("{left}{right}"))),
+        (left, right) => arithmetic(BinaryOp::Plus, left, right, i64::checked_add, |l, r| l + r),
+    }
+}
+
+fn arithmetic(
+    op: BinaryOp,
+    left: Value,
+    right: Value,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Value, ErrorKind> {
+    match (&left, &right) {
+        (Value::Int(l), Value::Int(r)) => int_op(*l, *r)
+            .map(Value::Int)
+            .ok_or(ErrorKind::IntegerOverflow),
+        (Value::Int(l), Value::Float(r)) => Ok(Value::Float(float_op(*l as f64, *r))),
+        (Value::Float(l), Value::Int(r)) => Ok(Value::Float(float_op(*l, *r as f64))),
+        (Value::Float(l), Value::Float(r)) => Ok(Value::Float(float_op(*l, *r))),
+        _ => Err(operands_error(op, &left, &right)),
+    }
+}
+
+fn integer(
+    op: BinaryOp,
+    left: Value,
+    right: Value,
+    int_op: fn(i64, i64) -> Result<i64, ErrorKind>,
+) -> Result<Value, ErrorKind> {
+    match (&left, &right) {
+        (Value::Int(l), Value::Int(r)) => int_op(*l, *r).map(Value::Int),
+        _ => Err(operands_error(op, &left, &right)),
+    }
+}
+
+fn compare(op: BinaryOp, left: Value, right: Value) -> Result<Value, ErrorKind> {
+    let ordering = match (&left, &right) {
+        (Value::Int(l), Value::Int(r)) => Some(l.cmp(r)),
+        (Value::Int(l), Value::Float(r)) => (*l as f64).partial_cmp(r),
+        (Value::Float(l), Value::Int(r)) => l.partial_cmp(&(*r as f64)),
+        (Value::Float(l), Value::Float(r)) => l.partial_cmp(r),
+        (Value::Str(l), Value::Str(r)) => Some(l.cmp(r)),
+        _ => return Err(operands_error(op, &left, &right)),
+    };
+
+    let result = match ordering {
+        Some(ordering) => match op {
+            BinaryOp::Gt => ordering.is_gt(),
+            BinaryOp::Gte => ordering.is_ge(),
+            BinaryOp::Lt => ordering.is_lt(),
+            BinaryOp::Lte => ordering.is_le(),
+            _ => unreachable!(),
+        },
+        None => false,
+    };
+
+    Ok(Value::Bool(result))
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Int(l), Value::Float(r)) => *l as f64 == *r,
+        (Value::Float(l), Value::Int(r)) => *l == *r as f64,
+        _ => left == right,
+    }
+}
+
+fn shift_amount(amount: i64) -> Result<u32, ErrorKind> {
+    match amount {
+        0..=63 => Ok(amount as u32),
+        _ => Err(ErrorKind::InvalidShift(amount)),
+    }
+}
+
+fn operands_error(op: BinaryOp, left: &Value, right: &Value) -> ErrorKind {
+    ErrorKind::BinaryOperands(BinaryOperandsError {
+        op,
+        left: left.type_name(),
+        right: right.type_name(),
+    })
+}
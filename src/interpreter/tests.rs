@@ -0,0 +1,345 @@
+use crate::{
+    lexer::Lexer,
+    parser::Parser,
+    source_map::SourceMap,
+};
+
+use super::*;
+
+fn interpret(input: &str) -> Result<Value, Error> {
+    let lexer = Lexer::new(input.to_string());
+    let ast = Parser::new(lexer).parse().unwrap();
+
+    Interpreter::new().interpret(&ast)
+}
+
+#[test]
+fn test_interpret_expressions() {
+    let test_cases = vec![
+        ("1", Value::Int(1)),
+        ("1.5", Value::Float(1.5)),
+        ("-9223372036854775808", Value::Int(i64::MIN)),
+        ("\"nya\"", Value::Str("nya".into())),
+        ("'nya' == \"nya\"", Value::Bool(true)),
+        ("let hp = 10; f\"HP: ${hp * 2}/${hp}\"", Value::Str("HP: 20/10".into())),
+        ("f\"${null} ${1.5} ${true} ${f\"${'x'}\"}\"", Value::Str("null 1.5 true x".into())),
+        ("let x = 0..=3; f\"${x} ${0..3}\"", Value::Str("0..=3 0..3".into())),
+        ("true", Value::Bool(true)),
+        ("null", Value::Null),
+        ("null == null", Value::Bool(true)),
+        ("null != 0", Value::Bool(true)),
+        ("!null", Value::Bool(true)),
+        ("\"x\" + null", Value::Str("xnull".into())),
+        ("2 + 3 * 4", Value::Int(14)),
+        ("(2 + 3) * 4", Value::Int(20)),
+        ("10 - 3 - 2", Value::Int(5)),
+        ("100 / 10 / 5", Value::Int(2)),
+        ("2 - 3 + 4", Value::Int(3)),
+        ("7 / 2", Value::Int(3)),
+        ("7 % 4", Value::Int(3)),
+        ("7 / 2.0", Value::Float(3.5)),
+        ("1 + 0.5", Value::Float(1.5)),
+        ("\"HP: \" + 100", Value::Str("HP: 100".into())),
+        ("-5", Value::Int(-5)),
+        ("!0", Value::Bool(true)),
+        ("~0", Value::Int(-1)),
+        ("6 & 3", Value::Int(2)),
+        ("6 | 3", Value::Int(7)),
+        ("6 ^ 3", Value::Int(5)),
+        ("1 << 4", Value::Int(16)),
+        ("-16 >> 2", Value::Int(-4)),
+        ("16 >>> 2", Value::Int(4)),
+        ("-16 >>> 60", Value::Int(15)),
+        ("-1 >>> 0", Value::Int(-1)),
+        ("-1 >>> 63", Value::Int(1)),
+        ("1 < 2", Value::Bool(true)),
+        ("2 <= 1.5", Value::Bool(false)),
+        ("\"a\" < \"b\"", Value::Bool(true)),
+        ("1 == 1.0", Value::Bool(true)),
+        ("\"a\" != \"a\"", Value::Bool(false)),
+        ("true && 0", Value::Bool(false)),
+        ("false || \"x\"", Value::Bool(true)),
+        ("true || undefined", Value::Bool(true)),
+        ("1 < 2 && 2 == 2", Value::Bool(true)),
+        ("\"nya\"[1]", Value::Str("y".into())),
+        ("false && undefined", Value::Bool(false)),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, interpret(tc.0).unwrap(), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_interpret_statements() {
+    let test_cases = vec![
+        ("let a = 5; a", Value::Int(5)),
+        ("let a; a", Value::Null),
+        ("let a = 1; a = 2; a", Value::Int(2)),
+        ("let a = 1; a += 2; a", Value::Int(3)),
+        ("let a = 3; a <<= 2; a", Value::Int(12)),
+        ("let a = -8; a >>>= 61; a", Value::Int(7)),
+        ("let a = 1; let b = a = 5; b", Value::Int(5)),
+        ("let a; let b; a = b = 3; a + b", Value::Int(6)),
+        ("return 42; 1", Value::Int(42)),
+        ("return", Value::Null),
+        ("let a = 1; { let a = 2; } a", Value::Int(1)),
+        ("let a = 1; { a = 2; } a", Value::Int(2)),
+        ("const a = 2; a * 3", Value::Int(6)),
+        ("const a = 1; { let a = 2; a = 3; } a", Value::Int(1)),
+        ("{ 1; 2 }", Value::Int(2)),
+        ("if (1 < 2) { \"yes\" } else { \"no\" }", Value::Str("yes".into())),
+        ("if (1 > 2) { \"yes\" } else { \"no\" }", Value::Str("no".into())),
+        ("if (false) { 1 }", Value::Null),
+        ("let x = if (true) { 1 } else { 2 }; x", Value::Int(1)),
+        ("if (true) { return 1; }; 2", Value::Int(1)),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, interpret(tc.0).unwrap(), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_interpret_functions() {
+    let test_cases = vec![
+        ("fn f() { return 1; } f()", Value::Int(1)),
+        ("fn f() { 1 } f()", Value::Null),
+        ("fn add(a, b) { return a + b; } add(2, 3)", Value::Int(5)),
+        ("let add = fn (a, b) { return a + b; }; add(2, 3)", Value::Int(5)),
+        ("fn (x) { return x * x; }(4)", Value::Int(16)),
+        ("((x, y) => x * y)(6, 7)", Value::Int(42)),
+        ("let f = () => { return 1; }; f()", Value::Int(1)),
+        ("let n = 2; let scale = (x) => x * n; scale(21)", Value::Int(42)),
+        (
+            r#"
+fn fib(n) {
+    if (n < 2) return n; else return fib(n - 1) + fib(n - 2);
+}
+fib(15)"#,
+            Value::Int(610),
+        ),
+        (
+            r#"
+fn counter() {
+    let count = 0;
+    return fn () { count += 1; return count; };
+}
+let next = counter();
+next();
+next();
+next()"#,
+            Value::Int(3),
+        ),
+        (
+            r#"
+let a = 1;
+fn get() { return a; }
+a = 2;
+get()"#,
+            Value::Int(2),
+        ),
+        (
+            r#"
+fn apply(f, x) { return f(x); }
+apply(fn (x) { return x + 1; }, 41)"#,
+            Value::Int(42),
+        ),
+        ("fn f(a) { a = 2; return a; } let a = 1; f(a); a", Value::Int(1)),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, interpret(tc.0).unwrap(), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_interpret_loops() {
+    let test_cases = vec![
+        ("let i = 0; while (i < 5) i += 1; i", Value::Int(5)),
+        ("let sum = 0; for (let i = 1; i <= 10; i += 1) sum += i; sum", Value::Int(55)),
+        ("let i = 0; for (; i < 3;) { i += 1; } i", Value::Int(3)),
+        ("let i = 0; for (;;) { i += 1; if (i == 7) break; } i", Value::Int(7)),
+        (
+            "let sum = 0; for (let i = 0; i < 10; i += 1) { if (i % 2 == 0) continue; sum += i; } sum",
+            Value::Int(25),
+        ),
+        (
+            "let n = 0; while (true) { let i = 0; while (true) { i += 1; if (i == 3) break; } n += i; if (n > 5) break; } n",
+            Value::Int(6),
+        ),
+        ("let i = 10; for (let i = 0; i < 3; i += 1) {} i", Value::Int(10)),
+        ("fn f() { while (true) { return 1; } } f()", Value::Int(1)),
+        ("while (false) {}", Value::Null),
+        ("let sum = 0; for (let i in 0..5) sum += i; sum", Value::Int(10)),
+        ("let sum = 0; for (let i in 1..=5) sum += i; sum", Value::Int(15)),
+        ("let n = 0; for (let i in 5..0) n += 1; n", Value::Int(0)),
+        ("let s = \"\"; for (let ch in \"nya\") s = ch + s; s", Value::Str("ayn".into())),
+        ("let i = 7; for (let i in 0..3) {} i", Value::Int(7)),
+        (
+            "let sum = 0; for (let i in 0..100) { if (i == 5) break; if (i % 2 == 1) continue; sum += i; } sum",
+            Value::Int(6),
+        ),
+        ("0..3", Value::Range { start: 0, end: 3, inclusive: false }),
+        ("0..=3", Value::Range { start: 0, end: 3, inclusive: true }),
+        ("let n = 0; for (let i in 3..=3) n += i; n", Value::Int(3)),
+        ("let n = 0; if (3..=3) n = 1; if (3..3) n = 2; n", Value::Int(1)),
+        (
+            "let n = 0; for (let i in 9223372036854775806..=9223372036854775807) n += 1; n",
+            Value::Int(2),
+        ),
+        (
+            r#"
+let total = 0;
+for (let i = 0; i < 3; i += 1) {
+    let f = fn () { return i; };
+    total += f();
+}
+total"#,
+            Value::Int(3),
+        ),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, interpret(tc.0).unwrap(), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_interpret_classes() {
+    let test_cases = vec![
+        (
+            r#"
+class Point {
+    x = 0;
+    y = 0;
+    constructor(x, y) { this.x = x; this.y = y; }
+    sum() { return this.x + this.y; }
+}
+Point(1, 2).sum()"#,
+            Value::Int(3),
+        ),
+        ("class A { n = 1; } let a = A(); let b = A(); a.n = 2; b.n", Value::Int(1)),
+        ("class A { n; } A().n", Value::Null),
+        (
+            r#"
+class Counter {
+    count = 0;
+    increment() { this.count += 1; return this; }
+}
+Counter().increment().increment().count"#,
+            Value::Int(2),
+        ),
+        (
+            r#"
+class Temperature {
+    celsius = 0;
+    get fahrenheit() { return this.celsius * 9 / 5 + 32; }
+    set fahrenheit(value) { this.celsius = (value - 32) * 5 / 9; }
+}
+let t = Temperature();
+t.fahrenheit = 212;
+t.celsius + t.fahrenheit"#,
+            Value::Int(312),
+        ),
+        (
+            r#"
+class Shape {
+    sides = 0;
+    constructor(name) { this.name = name; }
+    describe() { return this.name + " has " + this.sides + " sides"; }
+}
+class Square extends Shape {
+    sides = 4;
+}
+Square("square").describe()"#,
+            Value::Str("square has 4 sides".to_string()),
+        ),
+        (
+            r#"
+class Math {
+    static square(x) { return x * x; }
+    static get zero() { return 0; }
+}
+class More extends Math {}
+More.square(3) + Math.zero"#,
+            Value::Int(9),
+        ),
+        ("class A { f() { return this; } } let a = A(); let f = a.f; f() == a", Value::Bool(true)),
+        ("class A {} A() == A()", Value::Bool(false)),
+        ("let n = 1; class A { n = n + 1; } n = 5; A().n", Value::Int(6)),
+        ("class B { v = 0; } class A { b = B(); } let x = A(); x.b.v = 5; A().b.v", Value::Int(0)),
+        ("class A { a = 1; b = this.a + 1; } A().b", Value::Int(2)),
+        ("class A { a = 1; } class B extends A { b = this.a + 1; } B().b", Value::Int(2)),
+        ("class A { n = 1; constructor() { this.n = this.n + 1; } } A().n", Value::Int(2)),
+        ("let n = 0; class C { v = 1; } let c = C(); fn g() { n += 1; return c; } g().v += 1; n", Value::Int(1)),
+        ("let n = 0; class C { v = 1; } let c = C(); fn g() { n += 1; return c; } g().v += 1; c.v", Value::Int(2)),
+        ("class C { v = 1; get w() { return this.v; } set w(x) { this.v = x * 10; } } let c = C(); c.w += 1; c.v", Value::Int(20)),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, interpret(tc.0).unwrap(), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_interpret_errors() {
+    let test_cases = vec![
+        ("x", 0),
+        ("let a = 1;\na + b", 15),
+        ("b = 1", 0),
+        ("1 / 0", 0),
+        ("let a = 1;\n{ -\"nya\" }", 13),
+        ("true + 1", 0),
+        ("9223372036854775807 + 1", 0),
+        ("1 << 64", 0),
+        ("1 = 2", 0),
+        ("let f = 1;\nf(2)", 11),
+        ("\"nya\".len", 0),
+        ("\"nya\"[3]", 0),
+        ("let A = 1;\nclass B extends A {}", 27),
+        ("f\"a ${1} ${b}\"", 11),
+        ("class A { get x() { return y; } }\nA().x", 27),
+    ];
+
+    for tc in test_cases {
+        let err = interpret(tc.0).unwrap_err();
+
+        assert_eq!(Some(tc.1), err.span.map(|s| s.start), "{}", tc.0);
+    }
+
+    let input = "let a = 1;\na + b";
+    let err = interpret(input).unwrap_err();
+
+    assert_eq!("Runtime error at 2:5: undefined variable 'b'", err.display_with(&SourceMap::new(input)).to_string());
+    assert_eq!("Runtime error at 15..16: undefined variable 'b'", err.to_string());
+}
+
+#[test]
+fn test_interpret_error_kinds() {
+    assert!(matches!(interpret("x").unwrap_err().kind, ErrorKind::UndefinedVariable(name) if name == "x"));
+    assert!(matches!(interpret("null + 1").unwrap_err().kind, ErrorKind::BinaryOperands(_)));
+    assert!(matches!(interpret("1 % 0").unwrap_err().kind, ErrorKind::DivisionByZero));
+    assert!(matches!(interpret("1 = 2").unwrap_err().kind, ErrorKind::InvalidAssignmentTarget));
+    assert!(matches!(interpret("1 >> -1").unwrap_err().kind, ErrorKind::InvalidShift(-1)));
+    assert!(matches!(interpret("1 >>> 64").unwrap_err().kind, ErrorKind::InvalidShift(64)));
+
+    let (ast, _) = Parser::new(Lexer::new("let a = 1; a +;".to_string())).parse_recovering();
+    let err = Interpreter::new().interpret(&ast).unwrap_err();
+
+    assert!(matches!(err.kind, ErrorKind::InvalidStatement));
+    assert_eq!(Some(11), err.span.map(|s| s.start));
+    assert!(matches!(interpret("~1.5").unwrap_err().kind, ErrorKind::UnaryOperand(_)));
+    assert!(matches!(interpret("\"a\" * 2").unwrap_err().kind, ErrorKind::BinaryOperands(_)));
+    assert!(matches!(interpret("1(2)").unwrap_err().kind, ErrorKind::NotCallable("int")));
+    assert!(matches!(interpret("1[0]").unwrap_err().kind, ErrorKind::InvalidIndex(_)));
+    assert!(matches!(interpret("\"a\"[-1]").unwrap_err().kind, ErrorKind::IndexOutOfRange(-1)));
+    assert!(matches!(interpret("fn f(a) {} f()").unwrap_err().kind, ErrorKind::ArgumentCount(_)));
+    assert!(matches!(interpret("fn f() { return f(); } f()").unwrap_err().kind, ErrorKind::StackOverflow));
+    assert!(matches!(interpret("0..1.5").unwrap_err().kind, ErrorKind::RangeBounds(_)));
+    assert!(matches!(interpret("for (let i in 5) {}").unwrap_err().kind, ErrorKind::NotIterable("int")));
+    assert!(matches!(interpret("let A = 1; class B extends A {}").unwrap_err().kind, ErrorKind::InvalidSuperclass("int")));
+    assert!(matches!(interpret("class A {} A(1)").unwrap_err().kind, ErrorKind::ArgumentCount(_)));
+    assert!(matches!(interpret("class A {} A().f").unwrap_err().kind, ErrorKind::UndefinedProperty(_)));
+    assert!(matches!(interpret("class A { f() {} } A.f").unwrap_err().kind, ErrorKind::UndefinedProperty(_)));
+}
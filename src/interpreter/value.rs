@@ -0,0 +1,214 @@
+use std::{cell::RefCell, collections::HashMap, fmt, ptr, rc::Rc};
+
+use crate::ast::{Expression, Statement};
+
+use super::environment::Environment;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Null,
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Function(Rc<Closure>),
+    Range { start: i64, end: i64, inclusive: bool },
+    Class(Rc<ClassObject>),
+    Instance(Rc<RefCell<Instance>>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::Str(value) => write!(f, "{value}"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Function(closure) => write!(f, "{closure}"),
+            Value::Range { start, end, inclusive: false } => write!(f, "{start}..{end}"),
+            Value::Range { start, end, inclusive: true } => write!(f, "{start}..={end}"),
+            Value::Class(class) => write!(f, "{class}"),
+            Value::Instance(instance) => write!(f, "{}", instance.borrow()),
+        }
+    }
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::Function(_) => "function",
+            Value::Range { .. } => "range",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Int(value) => *value != 0,
+            Value::Float(value) => *value != 0.0,
+            Value::Str(value) => !value.is_empty(),
+            Value::Bool(value) => *value,
+            Value::Function(_) => true,
+            Value::Range { start, end, inclusive: false } => start < end,
+            Value::Range { start, end, inclusive: true } => start <= end,
+            Value::Class(_) | Value::Instance(_) => true,
+        }
+    }
+}
+
+// Function value together with the environment it was created in.
+pub struct Closure {
+    pub name: Option<String>,
+    pub parameters: Vec<String>,
+    pub body: Rc<Box<dyn Statement>>,
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl Closure {
+    /// Returns a copy of the closure which sees `this` as the given value.
+    pub fn bind(&self, this: Value) -> Closure {
+        let mut environment = Environment::with_parent(self.environment.clone());
+        environment.define("this", this);
+
+        Closure {
+            name: self.name.clone(),
+            parameters: self.parameters.clone(),
+            body: self.body.clone(),
+            environment: Rc::new(RefCell::new(environment)),
+        }
+    }
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Closure")
+            .field("name", &self.name)
+            .field("parameters", &self.parameters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {name}>"),
+            None => write!(f, "<fn>"),
+        }
+    }
+}
+
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Members {
+    pub methods: HashMap<String, Rc<Closure>>,
+    pub getters: HashMap<String, Rc<Closure>>,
+    pub setters: HashMap<String, Rc<Closure>>,
+}
+
+// Property name and initialiser of a class.
+pub type Field = (String, Option<Rc<Box<dyn Expression>>>);
+
+// Class value created by a class declaration.
+pub struct ClassObject {
+    pub name: String,
+    pub superclass: Option<Rc<ClassObject>>,
+    pub constructor: Option<Rc<Closure>>,
+    pub members: Members,
+    pub static_members: Members,
+    /// Property names and initialisers, evaluated for every new instance.
+    pub fields: Vec<Field>,
+    /// Environment the class was declared in, which initialisers see.
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl ClassObject {
+    pub fn members(&self, is_static: bool) -> &Members {
+        if is_static {
+            &self.static_members
+        } else {
+            &self.members
+        }
+    }
+
+    /// Looks a member up in this class first and then in its superclasses.
+    pub fn find<'a>(&'a self, select: impl Fn(&'a ClassObject) -> Option<&'a Rc<Closure>>) -> Option<Rc<Closure>> {
+        let mut class = Some(self);
+
+        while let Some(current) = class {
+            if let Some(closure) = select(current) {
+                return Some(closure.clone());
+            }
+
+            class = current.superclass.as_deref();
+        }
+
+        None
+    }
+}
+
+impl fmt::Debug for ClassObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClassObject")
+            .field("name", &self.name)
+            .field("superclass", &self.superclass.as_ref().map(|superclass| &superclass.name))
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for ClassObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<class {}>", self.name)
+    }
+}
+
+impl PartialEq for ClassObject {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+pub struct Instance {
+    pub class: Rc<ClassObject>,
+    pub fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<ClassObject>) -> Self {
+        Self { class, fields: HashMap::new() }
+    }
+}
+
+impl fmt::Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Fields may refer back to the instance itself.
+        f.debug_struct("Instance")
+            .field("class", &self.class.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
+
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
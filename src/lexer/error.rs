@@ -1,11 +1,15 @@
 use std::{error, fmt};
 
-use crate::span::*;
+use crate::{
+    source_map::SourceMap,
+    span::*,
+};
 
 #[derive(Debug, Clone)]
 pub enum ErrorKind {
     UnexpectedEof(UnexpectedEofError),
     UnexpectedChar(UnexpectedCharError),
+    UnterminatedComment(UnterminatedCommentError),
 }
 
 #[derive(Debug, Clone)]
@@ -16,13 +20,27 @@ pub struct Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Lexer error at {}:{}: ", self.span.position.line, self.span.position.column)
-            .and_then(|_| {
-                match &self.kind {
-                    ErrorKind::UnexpectedEof(err) => write!(f, "{err}"),
-                    ErrorKind::UnexpectedChar(err) => write!(f, "{err}"),
-                }
-            })
+        self.fmt_with(f, None)
+    }
+}
+
+impl Error {
+    /// Same message as `Display`, with the position as `line:column` instead of a byte range.
+    pub fn display_with<'a>(&'a self, source_map: &'a SourceMap<'_>) -> impl fmt::Display + 'a {
+        fmt::from_fn(move |f| self.fmt_with(f, Some(source_map)))
+    }
+
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, source_map: Option<&SourceMap<'_>>) -> fmt::Result {
+        match source_map {
+            Some(source_map) => write!(f, "Lexer error at {}: ", source_map.line_col(self.span.start))?,
+            None => write!(f, "Lexer error at {}: ", self.span)?,
+        }
+
+        match &self.kind {
+            ErrorKind::UnexpectedEof(err) => write!(f, "{err}"),
+            ErrorKind::UnexpectedChar(err) => write!(f, "{err}"),
+            ErrorKind::UnterminatedComment(err) => write!(f, "{err}"),
+        }
     }
 }
 
@@ -31,6 +49,7 @@ impl error::Error for Error {
         match &self.kind {
             ErrorKind::UnexpectedChar(err) => Some(err),
             ErrorKind::UnexpectedEof(err) => Some(err),
+            ErrorKind::UnterminatedComment(err) => Some(err),
         }
     }
 }
@@ -65,3 +84,14 @@ impl fmt::Display for UnexpectedEofError {
         write!(f, "unexpected EOF")
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct UnterminatedCommentError;
+
+impl error::Error for UnterminatedCommentError {}
+
+impl fmt::Display for UnterminatedCommentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unterminated block comment")
+    }
+}
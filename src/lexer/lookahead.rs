@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+
+/// Iterator adapter that can look any number of items ahead without consuming them.
+#[derive(Debug)]
+pub struct Lookahead<I: Iterator> {
+    iter: I,
+    buffer: VecDeque<I::Item>,
+}
+
+impl<I: Iterator> Lookahead<I> {
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Returns the item `n` positions ahead, `peek_nth(0)` being the one `next` returns.
+    pub fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        while self.buffer.len() <= n {
+            self.buffer.push_back(self.iter.next()?);
+        }
+
+        self.buffer.get(n)
+    }
+
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        self.peek_nth(0)
+    }
+
+    pub fn inner(&self) -> &I {
+        &self.iter
+    }
+}
+
+impl<I: Iterator> Iterator for Lookahead<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.pop_front().or_else(|| self.iter.next())
+    }
+}
@@ -1,12 +1,16 @@
 pub mod tokens;
+pub mod lookahead;
 pub mod error;
 
 #[cfg(test)]
 mod tests;
 
 pub use tokens::Tokens;
+pub use lookahead::Lookahead;
 pub use error::Error;
 
+use crate::source_map::SourceMap;
+
 #[derive(Debug)]
 pub struct Lexer {
     input: String
@@ -21,6 +25,27 @@ impl Lexer {
         Tokens::new(&self.input)
     }
 
+    /// Tokens carrying the whitespace and comments around them as trivia.
+    pub fn lossless_tokens(&self) -> Tokens<'_> {
+        Tokens::new_lossless(&self.input)
+    }
+
+    /// Tokens which report lexical errors through `Tokens::diagnostics` and
+    /// `Error` tokens instead of stopping at the first one.
+    pub fn recovering_tokens(&self) -> Tokens<'_> {
+        Tokens::new_recovering(&self.input)
+    }
+
+    /// Lossless tokens from a lexer which recovers from errors, so even input
+    /// with errors is spelled out in full.
+    pub fn lossless_recovering_tokens(&self) -> Tokens<'_> {
+        Tokens::new_lossless_recovering(&self.input)
+    }
+
+    pub fn source_map(&self) -> SourceMap<'_> {
+        SourceMap::new(&self.input)
+    }
+
     pub fn input_ref(&self) -> &str {
         &self.input
     }
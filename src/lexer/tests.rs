@@ -1,4 +1,5 @@
 use crate::{
+    source_map::LineCol,
     token::*,
     span::*
 };
@@ -25,88 +26,88 @@ print(a, b, c, greeting, ok, nope, nothing);
 "#;
     let test_cases = vec![
         // let a = 10;
-        make_token(Let, "let", Pos::new(1, 2, 1), 3),
-        make_token(Identifier, "a", Pos::new(5, 2, 5), 1),
-        make_token(Assign, "=", Pos::new(7, 2, 7), 1),
-        make_token(IntNumber, "10", Pos::new(9, 2, 9), 2),
-        make_token(Semicolon, ";", Pos::new(11, 2, 11), 1),
+        make_token(Let, "let", 1, 3),
+        make_token(Identifier, "a", 5, 1),
+        make_token(Assign, "=", 7, 1),
+        make_token(IntNumber, "10", 9, 2),
+        make_token(Semicolon, ";", 11, 1),
 
         // let b = 3.14;
-        make_token(Let, "let", Pos::new(13, 3, 1), 3),
-        make_token(Identifier, "b", Pos::new(17, 3, 5), 1),
-        make_token(Assign, "=", Pos::new(19, 3, 7), 1),
-        make_token(FloatNumber, "3.14", Pos::new(21, 3, 9), 4),
-        make_token(Semicolon, ";", Pos::new(25, 3, 13), 1),
+        make_token(Let, "let", 13, 3),
+        make_token(Identifier, "b", 17, 1),
+        make_token(Assign, "=", 19, 1),
+        make_token(FloatNumber, "3.14", 21, 4),
+        make_token(Semicolon, ";", 25, 1),
 
         // let c = a + b * 2;
-        make_token(Let, "let", Pos::new(27, 4, 1), 3),
-        make_token(Identifier, "c", Pos::new(31, 4, 5), 1),
-        make_token(Assign, "=", Pos::new(33, 4, 7), 1),
-        make_token(Identifier, "a", Pos::new(35, 4, 9), 1),
-        make_token(Plus, "+", Pos::new(37, 4, 11), 1),
-        make_token(Identifier, "b", Pos::new(39, 4, 13), 1),
-        make_token(Mult, "*", Pos::new(41, 4, 15), 1),
-        make_token(IntNumber, "2", Pos::new(43, 4, 17), 1),
-        make_token(Semicolon, ";", Pos::new(44, 4, 18), 1),
+        make_token(Let, "let", 27, 3),
+        make_token(Identifier, "c", 31, 1),
+        make_token(Assign, "=", 33, 1),
+        make_token(Identifier, "a", 35, 1),
+        make_token(Plus, "+", 37, 1),
+        make_token(Identifier, "b", 39, 1),
+        make_token(Mult, "*", 41, 1),
+        make_token(IntNumber, "2", 43, 1),
+        make_token(Semicolon, ";", 44, 1),
 
         // let name = "Alice";
-        make_token(Let, "let", Pos::new(47, 6, 1), 3),
-        make_token(Identifier, "name", Pos::new(51, 6, 5), 4),
-        make_token(Assign, "=", Pos::new(56, 6, 10), 1),
-        make_token(String, "\"Alice\"", Pos::new(58, 6, 12), 7),
-        make_token(Semicolon, ";", Pos::new(65, 6, 19), 1),
+        make_token(Let, "let", 47, 3),
+        make_token(Identifier, "name", 51, 4),
+        make_token(Assign, "=", 56, 1),
+        make_token(String, "\"Alice\"", 58, 7),
+        make_token(Semicolon, ";", 65, 1),
 
         // let greeting = "Hello, " + name;
-        make_token(Let, "let", Pos::new(67, 7, 1), 3),
-        make_token(Identifier, "greeting", Pos::new(71, 7, 5), 8),
-        make_token(Assign, "=", Pos::new(80, 7, 14), 1),
-        make_token(String, "\"Hello, \"", Pos::new(82, 7, 16), 9),
-        make_token(Plus, "+", Pos::new(92, 7, 26), 1),
-        make_token(Identifier, "name", Pos::new(94, 7, 28), 4),
-        make_token(Semicolon, ";", Pos::new(98, 7, 32), 1),
+        make_token(Let, "let", 67, 3),
+        make_token(Identifier, "greeting", 71, 8),
+        make_token(Assign, "=", 80, 1),
+        make_token(String, "\"Hello, \"", 82, 9),
+        make_token(Plus, "+", 92, 1),
+        make_token(Identifier, "name", 94, 4),
+        make_token(Semicolon, ";", 98, 1),
 
         // let ok = true;
-        make_token(Let, "let", Pos::new(101, 9, 1), 3),
-        make_token(Identifier, "ok", Pos::new(105, 9, 5), 2),
-        make_token(Assign, "=", Pos::new(108, 9, 8), 1),
-        make_token(True, "true", Pos::new(110, 9, 10), 4),
-        make_token(Semicolon, ";", Pos::new(114, 9, 14), 1),
+        make_token(Let, "let", 101, 3),
+        make_token(Identifier, "ok", 105, 2),
+        make_token(Assign, "=", 108, 1),
+        make_token(True, "true", 110, 4),
+        make_token(Semicolon, ";", 114, 1),
 
         // let nope = false;
-        make_token(Let, "let", Pos::new(116, 10, 1), 3),
-        make_token(Identifier, "nope", Pos::new(120, 10, 5), 4),
-        make_token(Assign, "=", Pos::new(125, 10, 10), 1),
-        make_token(False, "false", Pos::new(127, 10, 12), 5),
-        make_token(Semicolon, ";", Pos::new(132, 10, 17), 1),
+        make_token(Let, "let", 116, 3),
+        make_token(Identifier, "nope", 120, 4),
+        make_token(Assign, "=", 125, 1),
+        make_token(False, "false", 127, 5),
+        make_token(Semicolon, ";", 132, 1),
 
         // let nothing = null;
-        make_token(Let, "let", Pos::new(134, 11, 1), 3),
-        make_token(Identifier, "nothing", Pos::new(138, 11, 5), 7),
-        make_token(Assign, "=", Pos::new(146, 11, 13), 1),
-        make_token(Null, "null", Pos::new(148, 11, 15), 4),
-        make_token(Semicolon, ";", Pos::new(152, 11, 19), 1),
+        make_token(Let, "let", 134, 3),
+        make_token(Identifier, "nothing", 138, 7),
+        make_token(Assign, "=", 146, 1),
+        make_token(Null, "null", 148, 4),
+        make_token(Semicolon, ";", 152, 1),
 
         // print(a, b, c, greeting, ok, nope, nothing);
-        make_token(Identifier, "print", Pos::new(155, 13, 1), 5),
-        make_token(Lparen, "(", Pos::new(160, 13, 6), 1),
-        make_token(Identifier, "a", Pos::new(161, 13, 7), 1),
-        make_token(Comma, ",", Pos::new(162, 13, 8), 1),
-        make_token(Identifier, "b", Pos::new(164, 13, 10), 1),
-        make_token(Comma, ",", Pos::new(165, 13, 11), 1),
-        make_token(Identifier, "c", Pos::new(167, 13, 13), 1),
-        make_token(Comma, ",", Pos::new(168, 13, 14), 1),
-        make_token(Identifier, "greeting", Pos::new(170, 13, 16), 8),
-        make_token(Comma, ",", Pos::new(178, 13, 24), 1),
-        make_token(Identifier, "ok", Pos::new(180, 13, 26), 2),
-        make_token(Comma, ",", Pos::new(182, 13, 28), 1),
-        make_token(Identifier, "nope", Pos::new(184, 13, 30), 4),
-        make_token(Comma, ",", Pos::new(188, 13, 34), 1),
-        make_token(Identifier, "nothing", Pos::new(190, 13, 36), 7),
-        make_token(Rparen, ")", Pos::new(197, 13, 43), 1),
-        make_token(Semicolon, ";", Pos::new(198, 13, 44), 1),
+        make_token(Identifier, "print", 155, 5),
+        make_token(Lparen, "(", 160, 1),
+        make_token(Identifier, "a", 161, 1),
+        make_token(Comma, ",", 162, 1),
+        make_token(Identifier, "b", 164, 1),
+        make_token(Comma, ",", 165, 1),
+        make_token(Identifier, "c", 167, 1),
+        make_token(Comma, ",", 168, 1),
+        make_token(Identifier, "greeting", 170, 8),
+        make_token(Comma, ",", 178, 1),
+        make_token(Identifier, "ok", 180, 2),
+        make_token(Comma, ",", 182, 1),
+        make_token(Identifier, "nope", 184, 4),
+        make_token(Comma, ",", 188, 1),
+        make_token(Identifier, "nothing", 190, 7),
+        make_token(Rparen, ")", 197, 1),
+        make_token(Semicolon, ";", 198, 1),
 
         // EOF
-        make_token(Eof, "", Pos::new(200, 14, 1), 0),
+        make_token(Eof, "", 200, 0),
     ];
 
     let lexer = Lexer::new(input.to_string());
@@ -119,86 +120,490 @@ print(a, b, c, greeting, ok, nope, nothing);
     }
 }
 
+#[test]
+fn test_single_quoted_strings() {
+    let input = r#"
+let name = 'Alice';
+let quote = 'say "hi"' + "it's";
+let escaped = 'it\'s';
+"#;
+    let test_cases = vec![
+        // let name = 'Alice';
+        make_token(Let, "let", 1, 3),
+        make_token(Identifier, "name", 5, 4),
+        make_token(Assign, "=", 10, 1),
+        make_token(String, "'Alice'", 12, 7),
+        make_token(Semicolon, ";", 19, 1),
+
+        // let quote = 'say "hi"' + "it's";
+        make_token(Let, "let", 21, 3),
+        make_token(Identifier, "quote", 25, 5),
+        make_token(Assign, "=", 31, 1),
+        make_token(String, "'say \"hi\"'", 33, 10),
+        make_token(Plus, "+", 44, 1),
+        make_token(String, "\"it's\"", 46, 6),
+        make_token(Semicolon, ";", 52, 1),
+
+        // let escaped = 'it\'s';
+        make_token(Let, "let", 54, 3),
+        make_token(Identifier, "escaped", 58, 7),
+        make_token(Assign, "=", 66, 1),
+        make_token(String, "'it\\'s'", 68, 7),
+        make_token(Semicolon, ";", 75, 1),
+
+        // EOF
+        make_token(Eof, "", 77, 0),
+    ];
+
+    let lexer = Lexer::new(input.to_string());
+    let mut tokens = lexer.tokens();
+
+    for tc in test_cases {
+        let token = tokens.next_token().unwrap();
+
+        assert_eq!(tc, token);
+    }
+
+    let lexer = Lexer::new("'unterminated\"".to_string());
+    assert!(lexer.tokens().next_token().is_err());
+}
+
+#[test]
+fn test_fstrings() {
+    let input = r#"f"Hi ${name}, ${ {a} }${f"${b}"}!""#;
+    let test_cases = vec![
+        make_token(FStringStart, "f\"", 0, 2),
+        make_token(FStringText, "Hi ", 2, 3),
+        make_token(InterpolationStart, "${", 5, 2),
+        make_token(Identifier, "name", 7, 4),
+        make_token(InterpolationEnd, "}", 11, 1),
+        make_token(FStringText, ", ", 12, 2),
+        make_token(InterpolationStart, "${", 14, 2),
+        make_token(Lbrace, "{", 17, 1),
+        make_token(Identifier, "a", 18, 1),
+        make_token(Rbrace, "}", 19, 1),
+        make_token(InterpolationEnd, "}", 21, 1),
+        make_token(InterpolationStart, "${", 22, 2),
+        make_token(FStringStart, "f\"", 24, 2),
+        make_token(InterpolationStart, "${", 26, 2),
+        make_token(Identifier, "b", 28, 1),
+        make_token(InterpolationEnd, "}", 29, 1),
+        make_token(FStringEnd, "\"", 30, 1),
+        make_token(InterpolationEnd, "}", 31, 1),
+        make_token(FStringText, "!", 32, 1),
+        make_token(FStringEnd, "\"", 33, 1),
+        make_token(Eof, "", 34, 0),
+    ];
+
+    let lexer = Lexer::new(input.to_string());
+    let mut tokens = lexer.tokens();
+
+    for tc in test_cases {
+        let token = tokens.next_token().unwrap();
+
+        assert_eq!(tc, token);
+    }
+
+    let lexer = Lexer::new("f\"abc ${x}".to_string());
+    let mut tokens = lexer.tokens();
+    let err = (0..6).find_map(|_| tokens.next_token().err()).unwrap();
+
+    assert_eq!(10, err.span.start);
+}
+
+#[test]
+fn test_comments() {
+    let input = "a /* one\n/* two */ */ // line\n/**/b/ *c*/";
+    let test_cases = vec![
+        make_token(Identifier, "a", 0, 1),
+        make_token(MultiLineComment, "/* one\n/* two */ */", 2, 19),
+        make_token(SingleLineComment, "// line", 22, 7),
+        make_token(MultiLineComment, "/**/", 30, 4),
+        make_token(Identifier, "b", 34, 1),
+        make_token(Div, "/", 35, 1),
+        make_token(Mult, "*", 37, 1),
+        make_token(Identifier, "c", 38, 1),
+        make_token(Mult, "*", 39, 1),
+        make_token(Div, "/", 40, 1),
+        make_token(Eof, "", 41, 0),
+    ];
+
+    let lexer = Lexer::new(input.to_string());
+    let mut tokens = lexer.tokens();
+
+    for tc in test_cases {
+        let token = tokens.next_token().unwrap();
+
+        assert_eq!(tc, token);
+    }
+}
+
+#[test]
+fn test_unterminated_comment() {
+    let test_cases = vec![
+        ("/*", 0),
+        ("a\n  /* x", 4),
+        ("/* /* */", 0),
+        ("/* a */ /* /* b */ c", 8),
+        ("/*/", 0),
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let mut tokens = lexer.tokens();
+        let err = loop {
+            match tokens.next_token() {
+                Ok(token) if token.token_type == Eof => panic!("{}: expected an error", tc.0),
+                Ok(_) => {},
+                Err(err) => break err,
+            }
+        };
+
+        assert!(matches!(err.kind, error::ErrorKind::UnterminatedComment(_)), "{}", tc.0);
+        assert_eq!(Span::new(tc.1, 2), err.span, "{}", tc.0);
+    }
+
+    let input = "a\n  /* x";
+    let err = Lexer::new(input.to_string()).tokens().find_map(Result::err).unwrap();
+
+    assert_eq!("Lexer error at 2:3: unterminated block comment", err.display_with(&SourceMap::new(input)).to_string());
+}
+
+#[test]
+fn test_byte_spans() {
+    let input = "let здоровье = 'orc❤';\r\nx";
+    let test_cases = vec![
+        make_token(Let, "let", 0, 3),
+        make_token(Identifier, "здоровье", 4, 16),
+        make_token(Assign, "=", 21, 1),
+        make_token(String, "'orc❤'", 23, 8),
+        make_token(Semicolon, ";", 31, 1),
+        make_token(Identifier, "x", 34, 1),
+        make_token(Eof, "", 35, 0),
+    ];
+
+    let lexer = Lexer::new(input.to_string());
+    let source_map = lexer.source_map();
+    let mut tokens = lexer.tokens();
+
+    for tc in test_cases {
+        let token = tokens.next_token().unwrap();
+
+        assert_eq!(Some(token.lexeme.as_ref()), source_map.text(token.span.unwrap()));
+        assert_eq!(tc, token);
+    }
+}
+
+#[test]
+fn test_tokens_iterator() {
+    let lexer = Lexer::new("let x = 1;".to_string());
+    let token_types: Vec<_> = lexer.tokens().map(|token| token.unwrap().token_type).collect();
+
+    assert_eq!(vec![Let, Identifier, Assign, IntNumber, Semicolon, Eof], token_types);
+
+    // Iteration stops at the first error.
+    let lexer = Lexer::new("a /* b".to_string());
+    let results: Vec<_> = lexer.tokens().collect();
+
+    assert_eq!(2, results.len());
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_lookahead() {
+    let lexer = Lexer::new("(a, b) => a".to_string());
+    let mut tokens = Lookahead::new(lexer.tokens());
+
+    let token_type = |token: Option<&Result<Token, super::Error>>| token.map(|token| token.as_ref().unwrap().token_type);
+
+    assert_eq!(Some(Arrow), token_type(tokens.peek_nth(5)));
+    assert_eq!(Some(Comma), token_type(tokens.peek_nth(2)));
+    assert_eq!(Some(Lparen), token_type(tokens.peek()));
+    assert_eq!(None, token_type(tokens.peek_nth(8)));
+
+    let token_types: Vec<_> = tokens.map(|token| token.unwrap().token_type).collect();
+
+    assert_eq!(vec![Lparen, Identifier, Comma, Identifier, Rparen, Arrow, Identifier, Eof], token_types);
+}
+
+#[test]
+fn test_recovering_tokens() {
+    let test_cases = vec![
+        (
+            "let a = @1 # 2;",
+            vec![(Let, "let"), (Identifier, "a"), (Assign, "="), (TokenType::Error, "@"), (IntNumber, "1"), (TokenType::Error, "#"), (IntNumber, "2"), (Semicolon, ";"), (Eof, "")],
+            vec![Span::new(8, 1), Span::new(11, 1)],
+        ),
+        (
+            "a ¤ b",
+            vec![(Identifier, "a"), (TokenType::Error, "¤"), (Identifier, "b"), (Eof, "")],
+            vec![Span::new(2, 2)],
+        ),
+        (
+            "a \"bc",
+            vec![(Identifier, "a"), (TokenType::Error, "\"bc"), (Eof, "")],
+            vec![Span::new(2, 3)],
+        ),
+        (
+            "f\"a${b}c",
+            vec![(FStringStart, "f\""), (FStringText, "a"), (InterpolationStart, "${"), (Identifier, "b"), (InterpolationEnd, "}"), (TokenType::Error, "c"), (Eof, "")],
+            vec![Span::new(7, 1)],
+        ),
+        (
+            "a /* b",
+            vec![(Identifier, "a"), (MultiLineComment, "/* b"), (Eof, "")],
+            vec![Span::new(2, 2)],
+        ),
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let mut tokens = lexer.recovering_tokens();
+        let token_types: Vec<_> = tokens
+            .by_ref()
+            .map(|token| {
+                let token = token.unwrap();
+                (token.token_type, token.lexeme.into_owned())
+            })
+            .collect();
+        let spans: Vec<_> = tokens.diagnostics().iter().map(|error| error.span).collect();
+
+        assert_eq!(tc.1.iter().map(|(tt, lexeme)| (*tt, lexeme.to_string())).collect::<Vec<_>>(), token_types, "{}", tc.0);
+        assert_eq!(tc.2, spans, "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_lossless_round_trip() {
+    let inputs = vec![
+        "",
+        "   \n\t ",
+        "let a = 10; // ten\n",
+        "  /* lead */ fn f(a,b){\r\n  return a+b;   \n}\n\n// trailing comment",
+        "let s = f\"  x ${ a /* c */ + 1 }  y \";\n",
+        "/* outer /* inner */ */a\n\n\n  b  ",
+        "let name = \"Alice\";\nlet nothing = null;",
+    ];
+
+    for input in inputs {
+        let lexer = Lexer::new(input.to_string());
+        let mut tokens = lexer.lossless_tokens();
+        let mut source = std::string::String::new();
+
+        loop {
+            let token = tokens.next_token().unwrap();
+            source.push_str(&token.full_text());
+
+            if token.token_type == Eof {
+                break;
+            }
+        }
+
+        assert_eq!(input, source);
+    }
+}
+
+#[test]
+fn test_lossless_recovering_round_trip() {
+    let inputs = vec![
+        "let a = 1 ¤ 2;",
+        "a /* c */ ¤ // d\n  ¤\r\n b",
+        "f\"x ${ ¤ } y\"",
+        "let s = \"abc",
+        "a /* open",
+    ];
+
+    for input in inputs {
+        let lexer = Lexer::new(input.to_string());
+        let mut tokens = lexer.lossless_recovering_tokens();
+        let mut source = std::string::String::new();
+
+        loop {
+            let token = tokens.next_token().unwrap();
+            source.push_str(&token.full_text());
+
+            if token.token_type == Eof {
+                break;
+            }
+        }
+
+        assert_eq!(input, source);
+        assert!(!tokens.diagnostics().is_empty(), "{input}");
+    }
+
+    // The error token keeps the trivia around it.
+    let lexer = Lexer::new("a\n  /* c */ ¤ b".to_string());
+    let mut tokens = lexer.lossless_recovering_tokens();
+    tokens.next_token().unwrap();
+
+    let error = tokens.next_token().unwrap();
+    assert_eq!(Error, error.token_type);
+    assert_eq!("  /* c */ ¤ ", error.full_text());
+}
+
+#[test]
+fn test_newline_trivia() {
+    use TriviaKind::*;
+
+    let input = "a // one\r\nb\rc\n\r\nd";
+    let lexer = Lexer::new(input.to_string());
+    let tokens = lexer.lossless_tokens().map(Result::unwrap).collect::<Vec<_>>();
+
+    assert_eq!(
+        vec![Whitespace, SingleLineComment, Newline],
+        tokens[0].trailing_trivia.iter().map(|t| t.kind).collect::<Vec<_>>(),
+    );
+    assert_eq!("// one", tokens[0].trailing_trivia[1].text);
+
+    let newlines = tokens.iter()
+        .flat_map(|token| token.leading_trivia.iter().chain(&token.trailing_trivia))
+        .filter(|trivia| trivia.kind == Newline)
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["\r\n", "\r", "\n", "\r\n"], newlines.iter().map(|t| &*t.text).collect::<Vec<_>>());
+
+    // Every line break the lexer finds starts a new line of the source map.
+    let source_map = lexer.source_map();
+    assert_eq!(newlines.len() + 1, source_map.line_count());
+
+    for (line, newline) in newlines.iter().enumerate() {
+        assert_eq!(LineCol::new(line + 2, 1), source_map.line_col(newline.span.end()));
+    }
+}
+
+#[test]
+fn test_lossless_trivia() {
+    use TriviaKind::*;
+
+    let input = "a // one\n  /* two */ b  \n";
+    let lexer = Lexer::new(input.to_string());
+    let mut tokens = lexer.lossless_tokens();
+
+    let a = tokens.next_token().unwrap();
+    assert!(a.leading_trivia.is_empty());
+    assert_eq!(
+        vec![
+            Trivia::new(Whitespace, " ", Span::new(1, 1)),
+            Trivia::new(SingleLineComment, "// one", Span::new(2, 6)),
+            Trivia::new(Newline, "\n", Span::new(8, 1)),
+        ],
+        a.trailing_trivia,
+    );
+
+    let b = tokens.next_token().unwrap();
+    assert_eq!(make_token(Identifier, "b", 21, 1).span, b.span);
+    assert_eq!(
+        vec![
+            Trivia::new(Whitespace, "  ", Span::new(9, 2)),
+            Trivia::new(MultiLineComment, "/* two */", Span::new(11, 9)),
+            Trivia::new(Whitespace, " ", Span::new(20, 1)),
+        ],
+        b.leading_trivia,
+    );
+    assert_eq!(vec![Whitespace, Newline], b.trailing_trivia.iter().map(|t| t.kind).collect::<Vec<_>>());
+
+    let eof = tokens.next_token().unwrap();
+    assert_eq!(Eof, eof.token_type);
+    assert!(eof.leading_trivia.is_empty() && eof.trailing_trivia.is_empty());
+
+    // The default mode drops trivia and still reports comments as tokens.
+    let mut tokens = lexer.tokens();
+    let a = tokens.next_token().unwrap();
+
+    assert!(a.leading_trivia.is_empty() && a.trailing_trivia.is_empty());
+    assert_eq!(TokenType::SingleLineComment, tokens.next_token().unwrap().token_type);
+}
+
 #[test]
 fn test_numbers() {
     let cases = vec![
-        ("0", vec![make_token(IntNumber, "0", Pos::new(0, 1, 1), 1)]),
-        ("1", vec![make_token(IntNumber, "1", Pos::new(0, 1, 1), 1)]),
-        ("42", vec![make_token(IntNumber, "42", Pos::new(0, 1, 1), 2)]),
-        ("999999", vec![make_token(IntNumber, "999999", Pos::new(0, 1, 1), 6)]),
+        ("0", vec![make_token(IntNumber, "0", 0, 1)]),
+        ("1", vec![make_token(IntNumber, "1", 0, 1)]),
+        ("42", vec![make_token(IntNumber, "42", 0, 2)]),
+        ("999999", vec![make_token(IntNumber, "999999", 0, 6)]),
 
-        ("-1", vec![make_token(Minus, "-", Pos::new(0, 1, 1), 1), make_token(IntNumber, "1", Pos::new(1, 1, 2), 1)]),
-        ("-42", vec![make_token(Minus, "-", Pos::new(0, 1, 1), 1), make_token(IntNumber, "42", Pos::new(1, 1, 2), 2)]),
+        ("-1", vec![make_token(Minus, "-", 0, 1), make_token(IntNumber, "1", 1, 1)]),
+        ("-42", vec![make_token(Minus, "-", 0, 1), make_token(IntNumber, "42", 1, 2)]),
 
-        ("1_000", vec![make_token(IntNumber, "1_000", Pos::new(0, 1, 1), 5)]),
-        ("10_20_30", vec![make_token(IntNumber, "10_20_30", Pos::new(0, 1, 1), 8)]),
-        ("0_1_2", vec![make_token(IntNumber, "0_1_2", Pos::new(0, 1, 1), 5)]),
+        ("1_000", vec![make_token(IntNumber, "1_000", 0, 5)]),
+        ("10_20_30", vec![make_token(IntNumber, "10_20_30", 0, 8)]),
+        ("0_1_2", vec![make_token(IntNumber, "0_1_2", 0, 5)]),
 
         // Is not number
-        ("__123", vec![make_token(Identifier, "__123", Pos::new(0, 1, 1), 5)]),
+        ("__123", vec![make_token(Identifier, "__123", 0, 5)]),
+
+        ("0x0", vec![make_token(IntNumber, "0x0", 0, 3)]),
+        ("0xFF", vec![make_token(IntNumber, "0xFF", 0, 4)]),
+        ("0xdeadBEEF", vec![make_token(IntNumber, "0xdeadBEEF", 0, 10)]),
+        ("0xF_F", vec![make_token(IntNumber, "0xF_F", 0, 5)]),
 
-        ("0x0", vec![make_token(IntNumber, "0x0", Pos::new(0, 1, 1), 3)]),
-        ("0xFF", vec![make_token(IntNumber, "0xFF", Pos::new(0, 1, 1), 4)]),
-        ("0xdeadBEEF", vec![make_token(IntNumber, "0xdeadBEEF", Pos::new(0, 1, 1), 10)]),
-        ("0xF_F", vec![make_token(IntNumber, "0xF_F", Pos::new(0, 1, 1), 5)]),
+        ("0b0", vec![make_token(IntNumber, "0b0", 0, 3)]),
+        ("0b1010", vec![make_token(IntNumber, "0b1010", 0, 6)]),
+        ("0b10_11", vec![make_token(IntNumber, "0b10_11", 0, 7)]),
 
-        ("0b0", vec![make_token(IntNumber, "0b0", Pos::new(0, 1, 1), 3)]),
-        ("0b1010", vec![make_token(IntNumber, "0b1010", Pos::new(0, 1, 1), 6)]),
-        ("0b10_11", vec![make_token(IntNumber, "0b10_11", Pos::new(0, 1, 1), 7)]),
+        ("0o77", vec![make_token(IntNumber, "0o77", 0, 4)]),
+        ("0o1_2_3", vec![make_token(IntNumber, "0o1_2_3", 0, 7)]),
 
-        ("0o77", vec![make_token(IntNumber, "0o77", Pos::new(0, 1, 1), 4)]),
-        ("0o1_2_3", vec![make_token(IntNumber, "0o1_2_3", Pos::new(0, 1, 1), 7)]),
+        // Digits of another radix are left for the parser to reject.
+        ("0b12", vec![make_token(IntNumber, "0b12", 0, 4)]),
+        ("0xfg", vec![make_token(IntNumber, "0xfg", 0, 4)]),
 
-        ("0.0", vec![make_token(FloatNumber, "0.0", Pos::new(0, 1, 1), 3)]),
-        ("1.0", vec![make_token(FloatNumber, "1.0", Pos::new(0, 1, 1), 3)]),
-        ("42.5", vec![make_token(FloatNumber, "42.5", Pos::new(0, 1, 1), 4)]),
-        ("0.001", vec![make_token(FloatNumber, "0.001", Pos::new(0, 1, 1), 5)]),
+        ("0.0", vec![make_token(FloatNumber, "0.0", 0, 3)]),
+        ("1.0", vec![make_token(FloatNumber, "1.0", 0, 3)]),
+        ("42.5", vec![make_token(FloatNumber, "42.5", 0, 4)]),
+        ("0.001", vec![make_token(FloatNumber, "0.001", 0, 5)]),
 
-        ("5.", vec![make_token(FloatNumber, "5.", Pos::new(0, 1, 1), 2)]),
-        ("42.", vec![make_token(FloatNumber, "42.", Pos::new(0, 1, 1), 3)]),
+        ("5.", vec![make_token(FloatNumber, "5.", 0, 2)]),
+        ("42.", vec![make_token(FloatNumber, "42.", 0, 3)]),
 
-        ("1_000.5", vec![make_token(FloatNumber, "1_000.5", Pos::new(0, 1, 1), 7)]),
-        ("0.1_2_3", vec![make_token(FloatNumber, "0.1_2_3", Pos::new(0, 1, 1), 7)]),
-        ("1_2_3.4_5", vec![make_token(FloatNumber, "1_2_3.4_5", Pos::new(0, 1, 1), 9)]),
+        ("1_000.5", vec![make_token(FloatNumber, "1_000.5", 0, 7)]),
+        ("0.1_2_3", vec![make_token(FloatNumber, "0.1_2_3", 0, 7)]),
+        ("1_2_3.4_5", vec![make_token(FloatNumber, "1_2_3.4_5", 0, 9)]),
 
-        ("1e3", vec![make_token(FloatNumber, "1e3", Pos::new(0, 1, 1), 3)]),
-        ("1E3", vec![make_token(FloatNumber, "1E3", Pos::new(0, 1, 1), 3)]),
-        ("1.5e2", vec![make_token(FloatNumber, "1.5e2", Pos::new(0, 1, 1), 5)]),
-        ("1e-3", vec![make_token(FloatNumber, "1e-3", Pos::new(0, 1, 1), 4)]),
-        ("1e+3", vec![make_token(FloatNumber, "1e+3", Pos::new(0, 1, 1), 4)]),
+        ("1e3", vec![make_token(FloatNumber, "1e3", 0, 3)]),
+        ("1E3", vec![make_token(FloatNumber, "1E3", 0, 3)]),
+        ("1.5e2", vec![make_token(FloatNumber, "1.5e2", 0, 5)]),
+        ("1e-3", vec![make_token(FloatNumber, "1e-3", 0, 4)]),
+        ("1e+3", vec![make_token(FloatNumber, "1e+3", 0, 4)]),
 
         ("42.max", vec![
-            make_token(IntNumber, "42", Pos::new(0, 1, 1), 2),
-            make_token(Dot, ".", Pos::new(2, 1, 3), 1),
-            make_token(Identifier, "max", Pos::new(3, 1, 4), 3)
+            make_token(IntNumber, "42", 0, 2),
+            make_token(Dot, ".", 2, 1),
+            make_token(Identifier, "max", 3, 3)
         ]),
         ("10._bar", vec![
-            make_token(IntNumber, "10", Pos::new(0, 1, 1), 2),
-            make_token(Dot, ".", Pos::new(2, 1, 3), 1),
-            make_token(Identifier, "_bar", Pos::new(3, 1, 4), 4)
+            make_token(IntNumber, "10", 0, 2),
+            make_token(Dot, ".", 2, 1),
+            make_token(Identifier, "_bar", 3, 4)
         ]),
 
         ("1..5", vec![
-            make_token(IntNumber, "1", Pos::new(0, 1, 1), 1),
-            make_token(Range, "..", Pos::new(1, 1, 2), 2),
-            make_token(IntNumber, "5", Pos::new(3, 1, 4), 1)
+            make_token(IntNumber, "1", 0, 1),
+            make_token(Range, "..", 1, 2),
+            make_token(IntNumber, "5", 3, 1)
+        ]),
+
+        ("1..=5", vec![
+            make_token(IntNumber, "1", 0, 1),
+            make_token(RangeInclusive, "..=", 1, 3),
+            make_token(IntNumber, "5", 4, 1)
         ]),
 
         ("42..foo", vec![
-            make_token(IntNumber, "42", Pos::new(0, 1, 1), 2),
-            make_token(Range, "..", Pos::new(2, 1, 3), 2),
-            make_token(Identifier, "foo", Pos::new(4, 1, 5), 3)
+            make_token(IntNumber, "42", 0, 2),
+            make_token(Range, "..", 2, 2),
+            make_token(Identifier, "foo", 4, 3)
         ]),
 
         ("1.2.max", vec![
-            make_token(FloatNumber, "1.2", Pos::new(0, 1, 1), 3),
-            make_token(Dot, ".", Pos::new(3, 1, 4), 1),
-            make_token(Identifier, "max", Pos::new(4, 1, 5), 3)
+            make_token(FloatNumber, "1.2", 0, 3),
+            make_token(Dot, ".", 3, 1),
+            make_token(Identifier, "max", 4, 3)
         ]),
 
-        ("123_456_789.123_456_789", vec![make_token(FloatNumber, "123_456_789.123_456_789", Pos::new(0, 1, 1), 23)]),
-        ("0b1010_1010_1010_1010", vec![make_token(IntNumber, "0b1010_1010_1010_1010", Pos::new(0, 1, 1), 21)]),
-        ("123______________456", vec![make_token(IntNumber, "123______________456", Pos::new(0, 1, 1), 20)]),
+        ("123_456_789.123_456_789", vec![make_token(FloatNumber, "123_456_789.123_456_789", 0, 23)]),
+        ("0b1010_1010_1010_1010", vec![make_token(IntNumber, "0b1010_1010_1010_1010", 0, 21)]),
+        ("123______________456", vec![make_token(IntNumber, "123______________456", 0, 20)]),
     ];
 
     for tc in cases {
@@ -213,6 +618,35 @@ fn test_numbers() {
     }
 }
 
+#[test]
+fn test_brackets() {
+    let input = "items[0](a)[i + 1]";
+    let test_cases = vec![
+        make_token(Identifier, "items", 0, 5),
+        make_token(Lbracket, "[", 5, 1),
+        make_token(IntNumber, "0", 6, 1),
+        make_token(Rbracket, "]", 7, 1),
+        make_token(Lparen, "(", 8, 1),
+        make_token(Identifier, "a", 9, 1),
+        make_token(Rparen, ")", 10, 1),
+        make_token(Lbracket, "[", 11, 1),
+        make_token(Identifier, "i", 12, 1),
+        make_token(Plus, "+", 14, 1),
+        make_token(IntNumber, "1", 16, 1),
+        make_token(Rbracket, "]", 17, 1),
+        make_token(Eof, "", 18, 0),
+    ];
+
+    let lexer = Lexer::new(input.to_string());
+    let mut tokens = lexer.tokens();
+
+    for tc in test_cases {
+        let token = tokens.next_token().unwrap();
+
+        assert_eq!(tc, token);
+    }
+}
+
 #[test]
 fn test_ops() {
     let test_cases = [
@@ -230,6 +664,7 @@ fn test_ops() {
         ("/", Div),
         ("%", Mod),
         ("=", Assign),
+        ("=>", Arrow),
         ("+=", AssignPlus),
         ("-=", AssignMinus),
         ("*=", AssignMult),
@@ -240,12 +675,16 @@ fn test_ops() {
         ("^=", AssignBitXor),
         ("<<=", AssignShiftLeft),
         (">>=", AssignShiftRight),
+        (">>>=", AssignUnsignedShiftRight),
         ("|", BitOr),
         ("&", BitAnd),
         ("^", BitXor),
         ("<<", ShiftLeft),
         (">>", ShiftRight),
+        (">>>", UnsignedShiftRight),
         ("~", BitNot),
+        ("..", Range),
+        ("..=", RangeInclusive),
     ];
 
     for tc in test_cases {
@@ -270,10 +709,15 @@ fn test_keywords() {
         ("else", Else),
         ("for", For),
         ("while", While),
+        ("in", In),
         ("break", Break),
         ("continue", Continue),
         ("class", Class),
+        ("extends", Extends),
         ("static", Static),
+        ("constructor", Constructor),
+        ("get", Get),
+        ("set", Set),
         ("import", Import),
         ("from", From),
         ("export", Export),
@@ -294,6 +738,6 @@ fn test_keywords() {
     }
 }
 
-fn make_token(token_type: TokenType, lexeme: &str, start_pos: Pos, length: usize) -> Token {
-    Token::new_with_span(token_type, lexeme.into(), Span::new(start_pos, length))
+fn make_token(token_type: TokenType, lexeme: &str, start: usize, length: usize) -> Token<'_> {
+    Token::new_with_span(token_type, lexeme, Span::new(start, length))
 }
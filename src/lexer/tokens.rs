@@ -1,4 +1,4 @@
-use std::{str, mem};
+use std::{borrow::Cow, str};
 
 use crate::{
     token::*,
@@ -7,60 +7,242 @@ use crate::{
 
 use super::error::*;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    // Literal text of an f-string.
+    FString,
+    // Expression inside `${...}` with the number of braces it has opened itself.
+    Interpolation(usize),
+}
+
 #[derive(Debug)]
 pub struct Tokens<'a> {
+    source: &'a str,
     input: str::Chars<'a>,
-    index: usize,
-    lexeme: String,
+    // Byte offsets of the current lexeme in `source`.
+    lexeme_start: usize,
+    lexeme_end: usize,
     current_char: Option<char>,
     next_char: Option<char>,
-    line: usize,
-    column: usize,
-    token_position: Pos,
+    modes: Vec<Mode>,
+    lossless: bool,
+    recovering: bool,
+    diagnostics: Vec<Error>,
+    finished: bool,
 }
 
 impl<'a> Tokens<'a> {
-    pub(super) fn new(input: &'a String) -> Self {
-        let mut input = input.chars();
-        let index = 0;
-        let lexeme: String = Default::default();
+    pub(super) fn new(source: &'a str) -> Self {
+        let mut input = source.chars();
         let current_char = input.next();
         let next_char = input.next();
         
         Self {
+            source,
             input,
-            index,
-            lexeme,
+            lexeme_start: 0,
+            lexeme_end: 0,
             current_char,
             next_char,
-            line: 1,
-            column: 1,
-            token_position: Default::default(),
+            modes: vec![],
+            lossless: false,
+            recovering: false,
+            diagnostics: vec![],
+            finished: false,
+        }
+    }
+
+    /// Keeps whitespace and comments as trivia of the surrounding tokens instead
+    /// of dropping them, so the tokens together spell out the whole input.
+    pub(super) fn new_lossless(input: &'a str) -> Self {
+        Self {
+            lossless: true,
+            ..Self::new(input)
+        }
+    }
+
+    /// Turns lexical errors into `Error` tokens and keeps going, collecting the
+    /// errors in `diagnostics` instead of returning them.
+    pub(super) fn new_recovering(input: &'a str) -> Self {
+        Self {
+            recovering: true,
+            ..Self::new(input)
+        }
+    }
+
+    pub(super) fn new_lossless_recovering(input: &'a str) -> Self {
+        Self {
+            lossless: true,
+            recovering: true,
+            ..Self::new(input)
         }
     }
 }
 
-impl Tokens<'_> {
-    pub fn next_token(&mut self) -> Result<Token, Error> {
-        self.skip_whitespaces();
+/// Yields every token up to and including `Eof`, or up to the first error.
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<Token<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
 
-        self.lexeme.clear();
-        self.token_position = self.current_position();
+        let result = self.next_token();
+        self.finished = !matches!(&result, Ok(token) if token.token_type != TokenType::Eof);
 
-        self.read_keyword_or_identifier()
+        Some(result)
     }
+}
+
+impl<'a> Tokens<'a> {
+    pub fn next_token(&mut self) -> Result<Token<'a>, Error> {
+        // Read outside `read_token` so tokens made from an error keep their trivia too.
+        let leading_trivia = self.read_leading_trivia()?;
+
+        let mut token = match self.read_token() {
+            Err(error) if self.recovering => self.recover(error),
+            result => result?,
+        };
 
-    fn current_position(&self) -> Pos {
-        Pos::new(self.index, self.line, self.column)
+        if self.lossless && token.token_type != TokenType::Eof && !self.in_fstring_text() {
+            token.trailing_trivia = self.read_trivia(true)?;
+        }
+
+        token.leading_trivia = leading_trivia;
+
+        Ok(token)
+    }
+
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Errors met so far by a recovering lexer, in input order.
+    pub fn diagnostics(&self) -> &[Error] {
+        &self.diagnostics
+    }
+
+    fn read_token(&mut self) -> Result<Token<'a>, Error> {
+        self.lexeme_start = self.lexeme_end;
+
+        let mut token = if self.in_fstring_text() {
+            self.read_fstring_part()?
+        } else {
+            self.read_keyword_or_identifier()?
+        };
+
+        if let Some(Mode::Interpolation(depth)) = self.modes.last_mut() {
+            match token.token_type {
+                TokenType::Lbrace => *depth += 1,
+                TokenType::Rbrace if *depth > 0 => *depth -= 1,
+                TokenType::Rbrace => {
+                    self.modes.pop();
+                    token.token_type = TokenType::InterpolationEnd;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(token)
+    }
+
+    // Records the error and wraps whatever the failed token consumed into a token,
+    // so lexing resumes right after it.
+    fn recover(&mut self, error: Error) -> Token<'a> {
+        let token_type = match error.kind {
+            ErrorKind::UnexpectedChar(_) => TokenType::Error,
+            // An unterminated comment simply runs to the end of input.
+            ErrorKind::UnterminatedComment(_) => TokenType::MultiLineComment,
+            ErrorKind::UnexpectedEof(_) => TokenType::Error,
+        };
+
+        // Strings and f-strings only fail at the end of input, leaving nothing to close.
+        if self.current_char.is_none() {
+            self.modes.clear();
+        }
+
+        self.diagnostics.push(error);
+
+        self.make_token(token_type)
+    }
+
+    #[inline]
+    fn in_fstring_text(&self) -> bool {
+        self.modes.last() == Some(&Mode::FString)
+    }
+
+    fn read_leading_trivia(&mut self) -> Result<Vec<Trivia<'a>>, Error> {
+        if self.in_fstring_text() {
+            Ok(vec![])
+        } else if self.lossless {
+            self.read_trivia(false)
+        } else {
+            self.skip_whitespaces();
+            Ok(vec![])
+        }
+    }
+
+    // Trailing trivia ends with the first newline, the rest belongs to the next token.
+    fn read_trivia(&mut self, trailing: bool) -> Result<Vec<Trivia<'a>>, Error> {
+        let mut trivia = vec![];
+
+        loop {
+            self.lexeme_start = self.lexeme_end;
+
+            let (kind, text): (_, Cow<'a, str>) = match (self.current_char, self.next_char) {
+                (Some(ch), next) if is_new_line_char(ch) => {
+                    self.advance();
+
+                    if ch == '\r' && next == Some('\n') {
+                        self.advance();
+                    }
+
+                    (TriviaKind::Newline, self.lexeme().into())
+                }
+                (Some(ch), _) if is_whitespace_char(ch) => {
+                    while let Some(ch) = self.current_char
+                        && is_whitespace_char(ch)
+                        && !is_new_line_char(ch)
+                    {
+                        self.advance();
+                    }
+
+                    (TriviaKind::Whitespace, self.lexeme().into())
+                }
+                (Some('/'), Some('/')) => (TriviaKind::SingleLineComment, self.read_singleline_comment()?.lexeme),
+                (Some('/'), Some('*')) => match self.read_multiline_comment() {
+                    Ok(comment) => (TriviaKind::MultiLineComment, comment.lexeme),
+                    Err(error) if self.recovering => {
+                        self.diagnostics.push(error);
+                        (TriviaKind::MultiLineComment, self.lexeme().into())
+                    }
+                    Err(error) => return Err(error),
+                },
+                _ => break,
+            };
+
+            trivia.push(Trivia::new(kind, text, self.token_span()));
+
+            if trailing && kind == TriviaKind::Newline {
+                break;
+            }
+        }
+
+        Ok(trivia)
     }
 
     fn token_span(&self) -> Span {
-        Span::new(self.token_position, self.index - self.token_position.index)
+        Span::new(self.lexeme_start, self.lexeme_end - self.lexeme_start)
+    }
+
+    #[inline]
+    fn lexeme(&self) -> &'a str {
+        &self.source[self.lexeme_start..self.lexeme_end]
     }
 
-    fn make_token(&mut self, token_type: TokenType) -> Token {
-        let lexeme = mem::take(&mut self.lexeme);
-        Token::new_with_span(token_type, lexeme, self.token_span())
+    fn make_token(&self, token_type: TokenType) -> Token<'a> {
+        Token::new_with_span(token_type, self.lexeme(), self.token_span())
     }
 
     fn make_error(&self, error_kind: ErrorKind) -> Error {
@@ -68,24 +250,24 @@ impl Tokens<'_> {
     }
 
     fn advance(&mut self) {
-        match self.current_char {
-            Some(ch) if is_new_line_char(ch) => {
-                self.line += 1;
-                self.column = 1;
-            }
-            Some(_) => {
-                self.column += 1;
-            }
-            _ => return
-        }
+        let Some(ch) = self.current_char else {
+            return;
+        };
 
-        self.index += 1;
-        self.lexeme.push(self.current_char.unwrap());
+        self.lexeme_end += ch.len_utf8();
         self.current_char = self.next_char;
         self.next_char = self.input.next();
     }
 
-    fn read_keyword_or_identifier(&mut self) -> Result<Token, Error> {
+    fn read_keyword_or_identifier(&mut self) -> Result<Token<'a>, Error> {
+        if let (Some('f'), Some('"')) = (self.current_char, self.next_char) {
+            self.advance();
+            self.advance();
+            self.modes.push(Mode::FString);
+
+            return Ok(self.make_token(TokenType::FStringStart));
+        }
+
         match self.current_char {
             Some(ch) if is_idetifier_start_char(ch) => self.advance(),
             _ => { return self.read_number() },
@@ -98,7 +280,7 @@ impl Tokens<'_> {
             }
         }
 
-        let token_type = match self.lexeme.as_str() {
+        let token_type = match self.lexeme() {
             "let" => TokenType::Let,
             "const" => TokenType::Const,
             "fn" => TokenType::Fn,
@@ -109,10 +291,15 @@ impl Tokens<'_> {
             "else" => TokenType::Else,
             "for" => TokenType::For,
             "while" => TokenType::While,
+            "in" => TokenType::In,
             "break" => TokenType::Break,
             "continue" => TokenType::Continue,
             "class" => TokenType::Class,
+            "extends" => TokenType::Extends,
             "static" => TokenType::Static,
+            "constructor" => TokenType::Constructor,
+            "get" => TokenType::Get,
+            "set" => TokenType::Set,
             "import" => TokenType::Import,
             "from" => TokenType::From,
             "export" => TokenType::Export,
@@ -128,22 +315,14 @@ impl Tokens<'_> {
         Ok(self.make_token(token_type))
     }
 
-    fn read_number(&mut self) -> Result<Token, Error> {
+    fn read_number(&mut self) -> Result<Token<'a>, Error> {
         match self.current_char {
             Some('0') => {
                 self.advance();
                 match self.current_char {
-                    Some('x') => {
+                    Some('x' | 'b' | 'o') => {
                         self.advance();
-                        self.read_hexdecimal_number()
-                    }
-                    Some('b') => {
-                        self.advance();
-                        self.read_bindecimal_number()
-                    }
-                    Some('o') => {
-                        self.advance();
-                        self.read_octdecimal_number()
+                        self.read_radix_number()
                     }
                     _ => self.read_decimal_float_number(),
                 }
@@ -156,7 +335,7 @@ impl Tokens<'_> {
         }
     }
 
-    fn read_decimal_float_number(&mut self) -> Result<Token, Error> {
+    fn read_decimal_float_number(&mut self) -> Result<Token<'a>, Error> {
         loop {
             match self.current_char {
                 Some(ch) if is_number_char(ch) => self.advance(),
@@ -175,7 +354,7 @@ impl Tokens<'_> {
         self.read_decimal_number()
     }
 
-    fn read_decimal_number(&mut self) -> Result<Token, Error> {
+    fn read_decimal_number(&mut self) -> Result<Token<'a>, Error> {
         loop {
             match self.current_char {
                 Some(ch) if is_number_char(ch) => self.advance(),
@@ -186,32 +365,12 @@ impl Tokens<'_> {
         Ok(self.make_token(TokenType::IntNumber))
     }
 
-    fn read_hexdecimal_number(&mut self) -> Result<Token, Error> {
-        loop {
-            match self.current_char {
-                Some(ch) if is_hexdecimal_char(ch) => self.advance(),
-                _ => break,
-            }
-        }
-
-        Ok(self.make_token(TokenType::IntNumber))
-    }
-
-    fn read_octdecimal_number(&mut self) -> Result<Token, Error> {
-        loop {
-            match self.current_char {
-                Some(ch) if is_octdecimal_char(ch) => self.advance(),
-                _ => break,
-            }
-        }
-
-        Ok(self.make_token(TokenType::IntNumber))
-    }
-
-    fn read_bindecimal_number(&mut self) -> Result<Token, Error> {
+    // Reads every digit and letter after the prefix, leaving the parser to
+    // report the ones which are not digits of the radix.
+    fn read_radix_number(&mut self) -> Result<Token<'a>, Error> {
         loop {
             match self.current_char {
-                Some(ch) if is_bindecimal_char(ch) => self.advance(),
+                Some(ch) if is_radix_number_char(ch) => self.advance(),
                 _ => break,
             }
         }
@@ -219,7 +378,7 @@ impl Tokens<'_> {
         Ok(self.make_token(TokenType::IntNumber))
     }
 
-    fn read_float_number(&mut self) -> Result<Token, Error> {
+    fn read_float_number(&mut self) -> Result<Token<'a>, Error> {
         loop {
             match self.current_char {
                 Some(ch) if is_number_char(ch) => self.advance(),
@@ -237,9 +396,8 @@ impl Tokens<'_> {
                 Some('e' | 'E') => {
                     self.advance();
 
-                    match self.current_char {
-                        Some('+' | '-') => self.advance(),
-                        _ => {}
+                    if let Some('+' | '-') = self.current_char {
+                        self.advance();
                     }
 
                     break;
@@ -258,11 +416,12 @@ impl Tokens<'_> {
         Ok(self.make_token(TokenType::FloatNumber))
     }
 
-    fn read_string(&mut self) -> Result<Token, Error> {
-        match self.current_char {
-            Some('"') => self.advance(),
+    fn read_string(&mut self) -> Result<Token<'a>, Error> {
+        let quote = match self.current_char {
+            Some(quote @ ('"' | '\'')) => quote,
             _ => return self.read_sign(),
-        }
+        };
+        self.advance();
 
         loop {
             match self.current_char {
@@ -270,7 +429,7 @@ impl Tokens<'_> {
                     self.advance();
                     self.advance();
                 }
-                Some('"') => {
+                Some(ch) if ch == quote => {
                     self.advance();
                     break;
                 },
@@ -282,11 +441,54 @@ impl Tokens<'_> {
         Ok(self.make_token(TokenType::String))
     }
 
-    fn read_sign(&mut self) -> Result<Token, Error> {
+    // Reads the closing quote, the start of an interpolation or a run of text
+    // (escapes are kept as is and decoded by the parser).
+    fn read_fstring_part(&mut self) -> Result<Token<'a>, Error> {
+        match (self.current_char, self.next_char) {
+            (Some('"'), _) => {
+                self.advance();
+                self.modes.pop();
+
+                return Ok(self.make_token(TokenType::FStringEnd));
+            }
+            (Some('$'), Some('{')) => {
+                self.advance();
+                self.advance();
+                self.modes.push(Mode::Interpolation(0));
+
+                return Ok(self.make_token(TokenType::InterpolationStart));
+            }
+            _ => {}
+        }
+
+        loop {
+            match (self.current_char, self.next_char) {
+                (Some('"'), _) | (Some('$'), Some('{')) => break,
+                (Some('\\'), _) => {
+                    self.advance();
+                    self.advance();
+                }
+                (Some(_), _) => self.advance(),
+                (None, _) => return Err(self.make_error(ErrorKind::UnexpectedEof(UnexpectedEofError))),
+            }
+        }
+
+        Ok(self.make_token(TokenType::FStringText))
+    }
+
+    fn read_sign(&mut self) -> Result<Token<'a>, Error> {
         let token_type = match (self.current_char, self.next_char) {
-            (Some('.'), Some('.')) => self.advance_twice_and_return_tt(TokenType::Range),
+            (Some('.'), Some('.')) => {
+                self.advance();
+                self.advance();
+                match self.current_char {
+                    Some('=') => self.advance_and_return_tt(TokenType::RangeInclusive),
+                    _ => TokenType::Range
+                }
+            }
             (Some('.'), _) => self.advance_and_return_tt(TokenType::Dot),
             (Some('='), Some('=')) => self.advance_twice_and_return_tt(TokenType::Eq),
+            (Some('='), Some('>')) => self.advance_twice_and_return_tt(TokenType::Arrow),
             (Some('='), _) => self.advance_and_return_tt(TokenType::Assign),
             (Some('!'), Some('=')) => self.advance_twice_and_return_tt(TokenType::Neq),
             (Some('!'), _) => self.advance_and_return_tt(TokenType::Not),
@@ -309,6 +511,14 @@ impl Tokens<'_> {
                 self.advance();
                 match (self.current_char, self.next_char) {
                     (Some('='), _) => self.advance_and_return_tt(TokenType::Gte),
+                    (Some('>'), Some('>')) => {
+                        self.advance();
+                        self.advance();
+                        match self.current_char {
+                            Some('=') => self.advance_and_return_tt(TokenType::AssignUnsignedShiftRight),
+                            _ => TokenType::UnsignedShiftRight
+                        }
+                    }
                     (Some('>'), Some('=')) => self.advance_twice_and_return_tt(TokenType::AssignShiftRight),
                     (Some('>'), _) => self.advance_and_return_tt(TokenType::ShiftRight),
                     _ => TokenType::Gt
@@ -322,6 +532,7 @@ impl Tokens<'_> {
             (Some('*'), _) => self.advance_and_return_tt(TokenType::Mult),
             (Some('/'), Some('=')) => self.advance_twice_and_return_tt(TokenType::AssignDiv),
             (Some('/'), Some('/')) => return self.read_singleline_comment(),
+            (Some('/'), Some('*')) => return self.read_multiline_comment(),
             (Some('/'), _) => self.advance_and_return_tt(TokenType::Div),
             (Some('%'), Some('=')) => self.advance_twice_and_return_tt(TokenType::AssignMod),
             (Some('%'), _) => self.advance_and_return_tt(TokenType::Mod),
@@ -334,8 +545,12 @@ impl Tokens<'_> {
             (Some(')'), _) => self.advance_and_return_tt(TokenType::Rparen),
             (Some('{'), _) => self.advance_and_return_tt(TokenType::Lbrace),
             (Some('}'), _) => self.advance_and_return_tt(TokenType::Rbrace),
+            (Some('['), _) => self.advance_and_return_tt(TokenType::Lbracket),
+            (Some(']'), _) => self.advance_and_return_tt(TokenType::Rbracket),
             (None, _) => self.advance_and_return_tt(TokenType::Eof),
             (Some(ch), _) => {
+                // Consumed first so the error spans the char.
+                self.advance();
                 return Err(self.make_error(ErrorKind::UnexpectedChar(
                     UnexpectedCharError(ch)
                 )))
@@ -345,10 +560,10 @@ impl Tokens<'_> {
         Ok(self.make_token(token_type))
     }
 
-    fn read_singleline_comment(&mut self) -> Result<Token, Error> {
+    fn read_singleline_comment(&mut self) -> Result<Token<'a>, Error> {
         loop {
             match self.current_char {
-                Some('\n') => break,
+                Some(ch) if is_new_line_char(ch) => break,
                 None => break,
                 _ => self.advance(),
             }
@@ -357,6 +572,35 @@ impl Tokens<'_> {
         Ok(self.make_token(TokenType::SingleLineComment))
     }
 
+    fn read_multiline_comment(&mut self) -> Result<Token<'a>, Error> {
+        self.advance();
+        self.advance();
+
+        let mut depth = 1;
+
+        while depth > 0 {
+            match (self.current_char, self.next_char) {
+                (Some('/'), Some('*')) => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+                }
+                (Some(_), _) => self.advance(),
+                (None, _) => return Err(Error::new(
+                    ErrorKind::UnterminatedComment(UnterminatedCommentError),
+                    Span::new(self.lexeme_start, 2),
+                )),
+            }
+        }
+
+        Ok(self.make_token(TokenType::MultiLineComment))
+    }
+
     #[inline]
     fn advance_twice_and_return_tt(&mut self, token_type: TokenType) -> TokenType {
         self.advance();
@@ -407,22 +651,13 @@ fn is_whitespace_char(ch: char) -> bool {
     ch.is_whitespace()
 }
 
+// `\n`, `\r\n` and a lone `\r` all end a line, as in `SourceMap`.
 #[inline]
 fn is_new_line_char(ch: char) -> bool {
-    ch == '\n'
-}
-
-#[inline]
-fn is_hexdecimal_char(ch: char) -> bool {
-    ch.is_ascii_digit() || matches!(ch, 'a'..='f' | 'A'..='F' | '_')
-}
-
-#[inline]
-fn is_bindecimal_char(ch: char) -> bool {
-    matches!(ch, '0' | '1' | '_')
+    matches!(ch, '\n' | '\r')
 }
 
 #[inline]
-fn is_octdecimal_char(ch: char) -> bool {
-    matches!(ch, '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '_')
+fn is_radix_number_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
 }
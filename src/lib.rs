@@ -3,3 +3,5 @@ pub mod lexer;
 pub mod ast;
 pub mod parser;
 pub mod span;
+pub mod source_map;
+pub mod interpreter;
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::ast::{
+    arena::{ClassMember, FStringPart},
+    Arena, ExprKind, NodeId, NodeKind, StmtKind,
+};
+
+use super::error::*;
+
+/// Rejects every assignment whose target resolves to a `const` binding.
+pub fn check_const_assignments(arena: &Arena) -> Result<(), Error> {
+    match check_all_const_assignments(arena).into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Like `check_const_assignments`, returning every violation in source order.
+pub fn check_all_const_assignments(arena: &Arena) -> Vec<Error> {
+    let mut checker = ConstChecker::new(arena);
+
+    for &statement in arena.statements() {
+        checker.check_statement(statement);
+    }
+
+    checker.finish()
+}
+
+// Checks a lone expression or statement of `arena`.
+pub(super) fn check_node_const_assignments(arena: &Arena, id: NodeId) -> Result<(), Error> {
+    let mut checker = ConstChecker::new(arena);
+
+    match arena.get(id).map(|node| &node.kind) {
+        Some(NodeKind::Expression(_)) => checker.check_expression(id),
+        Some(NodeKind::Statement(_)) => checker.check_statement(id),
+        None => {}
+    }
+
+    match checker.finish().into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Default)]
+struct Scope<'a> {
+    parent: Option<usize>,
+    // Whether each name was declared `const`.
+    bindings: HashMap<&'a str, bool>,
+}
+
+// Walks the tree keeping every lexical scope it enters. Function bodies and
+// property initialisers run whenever they are called or a class instantiated,
+// so they are checked last, against the scopes around them as those are at
+// their end, including bindings declared after the function or class.
+#[derive(Debug)]
+struct ConstChecker<'a> {
+    arena: &'a Arena<'a>,
+    scopes: Vec<Scope<'a>>,
+    current: usize,
+    deferred: Vec<(usize, &'a [NodeId], NodeId)>,
+    errors: Vec<Error>,
+}
+
+impl<'a> ConstChecker<'a> {
+    fn new(arena: &'a Arena<'a>) -> Self {
+        Self {
+            arena,
+            scopes: vec![Scope::default()],
+            current: 0,
+            deferred: vec![],
+            errors: vec![],
+        }
+    }
+
+    fn finish(mut self) -> Vec<Error> {
+        let mut index = 0;
+
+        while let Some(&(scope, parameters, body)) = self.deferred.get(index) {
+            self.current = scope;
+            self.in_scope(|checker| {
+                for &parameter in parameters {
+                    checker.declare(parameter, false);
+                }
+
+                match checker.arena.get(body).map(|node| &node.kind) {
+                    Some(NodeKind::Expression(_)) => checker.check_expression(body),
+                    Some(NodeKind::Statement(_)) => checker.check_statement(body),
+                    None => {}
+                }
+            });
+            index += 1;
+        }
+
+        self.errors.sort_by_key(|err| err.span.map(|span| span.start));
+        self.errors
+    }
+
+    fn identifier(&self, id: NodeId) -> Option<&'a str> {
+        match self.arena.expression(id) {
+            Some(ExprKind::Identifier(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn declare(&mut self, identifier: NodeId, is_const: bool) {
+        if let Some(name) = self.identifier(identifier) {
+            self.scopes[self.current].bindings.insert(name, is_const);
+        }
+    }
+
+    fn is_const(&self, name: &str) -> bool {
+        let mut scope = Some(self.current);
+
+        while let Some(index) = scope {
+            if let Some(&is_const) = self.scopes[index].bindings.get(name) {
+                return is_const;
+            }
+
+            scope = self.scopes[index].parent;
+        }
+
+        false
+    }
+
+    fn in_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        let parent = self.current;
+        self.scopes.push(Scope { parent: Some(parent), bindings: HashMap::new() });
+        self.current = self.scopes.len() - 1;
+        f(self);
+        self.current = parent;
+    }
+
+    // Queues a function body or property initialiser for `finish`.
+    fn defer(&mut self, parameters: &'a [NodeId], body: NodeId) {
+        self.deferred.push((self.current, parameters, body));
+    }
+
+    fn check_statement(&mut self, id: NodeId) {
+        let Some(kind) = self.arena.statement(id) else {
+            return;
+        };
+
+        match kind {
+            StmtKind::Let { identifier, expression } => {
+                if let Some(expression) = *expression {
+                    self.check_expression(expression);
+                }
+
+                self.declare(*identifier, false);
+            }
+            StmtKind::Const { identifier, expression } => {
+                self.check_expression(*expression);
+                self.declare(*identifier, true);
+            }
+            StmtKind::Return(expression) => {
+                if let Some(expression) = *expression {
+                    self.check_expression(expression);
+                }
+            }
+            StmtKind::Expr(expression) => self.check_expression(*expression),
+            StmtKind::Block(statements) => {
+                self.in_scope(|checker| {
+                    for &statement in statements {
+                        checker.check_statement(statement);
+                    }
+                });
+            }
+            StmtKind::Function { name, parameters, body } => {
+                self.declare(*name, false);
+                self.defer(parameters, *body);
+            }
+            StmtKind::While { condition, body } => {
+                self.check_expression(*condition);
+                self.check_statement(*body);
+            }
+            StmtKind::For { initializer, condition, update, body } => {
+                self.in_scope(|checker| {
+                    if let Some(initializer) = *initializer {
+                        checker.check_statement(initializer);
+                    }
+
+                    if let Some(condition) = *condition {
+                        checker.check_expression(condition);
+                    }
+
+                    if let Some(update) = *update {
+                        checker.check_expression(update);
+                    }
+
+                    checker.check_statement(*body);
+                });
+            }
+            StmtKind::ForIn { variable, iterable, body } => {
+                self.check_expression(*iterable);
+
+                self.in_scope(|checker| {
+                    checker.declare(*variable, false);
+                    checker.check_statement(*body);
+                });
+            }
+            StmtKind::Break | StmtKind::Continue | StmtKind::Error => {}
+            StmtKind::Class { name, superclass, members } => {
+                if let Some(superclass) = *superclass {
+                    self.check_expression(superclass);
+                }
+
+                self.declare(*name, false);
+
+                for member in members {
+                    match member {
+                        ClassMember::Method(method) => self.defer(&method.parameters, method.body),
+                        ClassMember::Property(property) => if let Some(value) = property.value {
+                            self.defer(&[], value);
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_expression(&mut self, id: NodeId) {
+        let Some(kind) = self.arena.expression(id) else {
+            return;
+        };
+
+        match kind {
+            ExprKind::Identifier(_) | ExprKind::Literal(_) => {}
+            ExprKind::Binary { left, op, right } => {
+                if op.is_assignment()
+                    && let Some(name) = self.identifier(*left)
+                    && self.is_const(name)
+                {
+                    self.errors.push(Error::new(
+                        ErrorKind::ConstAssignment(name.to_string()),
+                        self.arena.span(*left),
+                    ));
+                }
+
+                self.check_expression(*left);
+                self.check_expression(*right);
+            }
+            ExprKind::Unary { right, .. } => self.check_expression(*right),
+            ExprKind::If { condition, consequence, alternative } => {
+                self.check_expression(*condition);
+                self.check_statement(*consequence);
+
+                if let Some(alternative) = *alternative {
+                    self.check_statement(alternative);
+                }
+            }
+            ExprKind::Call { callee, arguments } => {
+                self.check_expression(*callee);
+
+                for &argument in arguments {
+                    self.check_expression(argument);
+                }
+            }
+            ExprKind::Member { object, .. } => self.check_expression(*object),
+            ExprKind::Index { object, index } => {
+                self.check_expression(*object);
+                self.check_expression(*index);
+            }
+            ExprKind::FunctionLiteral { parameters, body } => self.defer(parameters, *body),
+            ExprKind::Range { start, end, .. } => {
+                self.check_expression(*start);
+                self.check_expression(*end);
+            }
+            ExprKind::FString(parts) => {
+                for part in parts {
+                    if let FStringPart::Expression(expression) = *part {
+                        self.check_expression(expression);
+                    }
+                }
+            }
+        }
+    }
+}
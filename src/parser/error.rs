@@ -2,6 +2,7 @@ use std::{fmt, error, num};
 
 use crate::{
     lexer::Error as LexerError,
+    source_map::SourceMap,
     span::*,
     token::*,
 };
@@ -13,9 +14,13 @@ pub enum ErrorKind {
     ExpectExpression(UnexpectedTokenError),
     ExpectTerminal(UnexpectedTokenError),
     ExpectStatement(UnexpectedTokenError),
-    ParseInt(num::ParseIntError),
+    MalformedInt(MalformedIntError),
+    IntOverflow,
     ParseString(ParseStringError),
     ParseFloat(num::ParseFloatError),
+    OutsideLoop(Box<Token<'static>>),
+    ConstWithoutInitializer(String),
+    ConstAssignment(String),
 }
 
 #[derive(Debug, Clone)]
@@ -26,23 +31,52 @@ pub struct Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, None)
+    }
+}
+
+impl Error {
+    pub(super) fn new(kind: ErrorKind, span: Option<Span>) -> Self {
+        Self { kind, span }
+    }
+
+    pub(super) fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Same message as `Display`, with positions as `line:column` instead of byte ranges.
+    pub fn display_with<'a>(&'a self, source_map: &'a SourceMap<'_>) -> impl fmt::Display + 'a {
+        fmt::from_fn(move |f| self.fmt_with(f, Some(source_map)))
+    }
+
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, source_map: Option<&SourceMap<'_>>) -> fmt::Result {
         write!(f, "Parse error")?;
 
-        if let Some(span) = self.span {
-            write!(f, " at {}:{}", span.position.line, span.position.column)?;
+        match (self.span, source_map) {
+            (Some(span), Some(source_map)) => write!(f, " at {}", source_map.line_col(span.start))?,
+            (Some(span), None) => write!(f, " at {span}")?,
+            (None, _) => {}
         }
 
         write!(f, ": ")?;
 
         match &self.kind {
-            ErrorKind::Lexer(err) => write!(f, "{err}"),
+            ErrorKind::Lexer(err) => match source_map {
+                Some(source_map) => write!(f, "{}", err.display_with(source_map)),
+                None => write!(f, "{err}"),
+            },
             ErrorKind::UnexpectedToken(err) => write!(f, "{err}"),
-            ErrorKind::ParseInt(err) => write!(f, "{err}"),
+            ErrorKind::MalformedInt(err) => write!(f, "{err}"),
+            ErrorKind::IntOverflow => write!(f, "integer literal is too large"),
             ErrorKind::ParseString(err) => write!(f, "{err}"),
             ErrorKind::ParseFloat(err) => write!(f, "{err}"),
             ErrorKind::ExpectExpression(err) => write!(f, "expect expression, got {}", err.token),
             ErrorKind::ExpectTerminal(err) => write!(f, "expect terminal, got {}", err.token),
             ErrorKind::ExpectStatement(err) => write!(f, "expect statement, got {}", err.token),
+            ErrorKind::OutsideLoop(token) => write!(f, "'{}' outside of loop", token.lexeme),
+            ErrorKind::ConstWithoutInitializer(name) => write!(f, "missing initializer for const '{name}'"),
+            ErrorKind::ConstAssignment(name) => write!(f, "assignment to const '{name}'"),
         }
     }
 }
@@ -51,6 +85,7 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match &self.kind {
             ErrorKind::Lexer(err) => Some(err),
+            ErrorKind::MalformedInt(err) => Some(err),
             _ => None,
         }
     }
@@ -63,12 +98,6 @@ impl From<LexerError> for Error {
     }
 }
 
-impl From<num::ParseIntError> for Error {
-    fn from(value: num::ParseIntError) -> Self {
-        Self::new(ErrorKind::ParseInt(value), None)
-    }
-}
-
 impl From<ParseStringError> for Error {
     fn from(value: ParseStringError) -> Self {
         Self::new(ErrorKind::ParseString(value), None)
@@ -87,22 +116,10 @@ impl From<UnexpectedTokenError> for Error {
     }
 }
 
-impl Error {
-    pub(super) fn new(kind: ErrorKind, span: Option<Span>) -> Self {
-        Self { kind, span }
-    }
-
-    pub(super) fn with_span(mut self, span: Option<Span>) -> Self {
-        self.span = span;
-        self
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseStringError {
     UnexpectedEnding,
     UnexpectedChar(char),
-    ParseInt(num::ParseIntError),
     UndefinedUnicode(u32),
 }
 
@@ -113,30 +130,43 @@ impl fmt::Display for ParseStringError {
         match self {
             UnexpectedEnding => write!(f, "unexpected string ending"),
             UnexpectedChar(ch) => write!(f, "unexpected char \'{ch}\'"),
-            ParseInt(err) => write!(f, "{err}"),
             UndefinedUnicode(u) => write!(f, "undefined unicode: {u}"),
         }
     }
 }
 
-impl From<num::ParseIntError> for ParseStringError {
-    fn from(value: num::ParseIntError) -> Self {
-        Self::ParseInt(value)
-    }
+impl error::Error for ParseStringError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MalformedIntError {
+    MissingDigits,
+    LeadingSeparator,
+    TrailingSeparator,
+    ConsecutiveSeparators,
+    InvalidDigit(char),
 }
 
-impl error::Error for ParseStringError {
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+impl fmt::Display for MalformedIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use MalformedIntError::*;
+
+        write!(f, "malformed integer literal: ")?;
+
         match self {
-            ParseStringError::ParseInt(err) => Some(err),
-            _ => None,
+            MissingDigits => write!(f, "no digits after the radix prefix"),
+            LeadingSeparator => write!(f, "'_' before the first digit"),
+            TrailingSeparator => write!(f, "'_' after the last digit"),
+            ConsecutiveSeparators => write!(f, "consecutive '_' separators"),
+            InvalidDigit(ch) => write!(f, "invalid digit \'{ch}\'"),
         }
     }
 }
 
+impl error::Error for MalformedIntError {}
+
 #[derive(Debug, Clone)]
 pub struct UnexpectedTokenError {
-    pub token: Token,
+    pub token: Box<Token<'static>>,
     pub expected: Vec<TokenType>,
 }
 
@@ -144,7 +174,7 @@ impl fmt::Display for UnexpectedTokenError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "unexpected token {}", self.token)?;
 
-        if self.expected.len() > 0 {
+        if !self.expected.is_empty() {
             let token_types_string = self.expected
                 .iter()
                 .map(|tt| tt.to_string())
@@ -1,14 +1,17 @@
 pub mod error;
 mod tokens_parser;
+mod const_checker;
 
 #[cfg(test)]
 mod tests;
 
 use crate::{
-    ast::Ast, lexer::Lexer,
+    ast::{Arena, Ast, Expression, Statement}, lexer::Lexer,
 };
 
 pub use error::Error;
+pub use const_checker::{check_all_const_assignments, check_const_assignments};
+use const_checker::check_node_const_assignments;
 use tokens_parser::TokensParser;
 
 #[derive(Debug)]
@@ -21,7 +24,64 @@ impl Parser {
         Self { lexer }
     }
 
+    /// Parses the whole program and checks that no `const` binding is reassigned.
     pub fn parse(&self) -> Result<Ast, Error> {
-        TokensParser::new(self.lexer.tokens()).parse()
+        Ok(self.parse_arena()?.into_ast())
+    }
+
+    /// Like `parse`, leaving the program in an `Arena` which borrows its tokens
+    /// from the lexer's input.
+    pub fn parse_arena(&self) -> Result<Arena<'_>, Error> {
+        let arena = TokensParser::new(self.lexer.tokens()).parse()?;
+        check_const_assignments(&arena)?;
+
+        Ok(arena)
+    }
+
+    /// Parses a lone expression, without a trailing `;`, which has to make up
+    /// the whole input.
+    pub fn parse_expression(input: &str) -> Result<Box<dyn Expression>, Error> {
+        let lexer = Lexer::new(input.to_string());
+        let (arena, expression) = TokensParser::new(lexer.tokens()).parse_single_expression()?;
+        check_node_const_assignments(&arena, expression)?;
+
+        Ok(arena.into_expression(expression))
+    }
+
+    /// Parses a single statement which has to make up the whole input.
+    pub fn parse_statement(input: &str) -> Result<Box<dyn Statement>, Error> {
+        let lexer = Lexer::new(input.to_string());
+        let (arena, statement) = TokensParser::new(lexer.tokens()).parse_single_statement()?;
+        check_node_const_assignments(&arena, statement)?;
+
+        Ok(arena.into_statement(statement))
+    }
+
+    /// Parses a single `{ ... }` block which has to make up the whole input.
+    pub fn parse_block(input: &str) -> Result<Box<dyn Statement>, Error> {
+        let lexer = Lexer::new(input.to_string());
+        let (arena, block) = TokensParser::new(lexer.tokens()).parse_single_block()?;
+        check_node_const_assignments(&arena, block)?;
+
+        Ok(arena.into_statement(block))
+    }
+
+    /// Parses as much of the program as possible and returns every lexer, parser
+    /// and `const` error found, ordered by position. Statements which failed to
+    /// parse are left in the tree as `ast::ErrorStatement`s.
+    pub fn parse_recovering(&self) -> (Ast, Vec<Error>) {
+        let (arena, errors) = self.parse_arena_recovering();
+
+        (arena.into_ast(), errors)
+    }
+
+    /// Like `parse_recovering`, leaving the program in an `Arena`.
+    pub fn parse_arena_recovering(&self) -> (Arena<'_>, Vec<Error>) {
+        let (arena, mut errors) = TokensParser::new(self.lexer.recovering_tokens()).parse_recovering();
+
+        errors.extend(check_all_const_assignments(&arena));
+        errors.sort_by_key(|err| err.span.map(|span| span.start));
+
+        (arena, errors)
     }
 }
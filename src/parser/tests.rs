@@ -1,143 +1,242 @@
-use std::{fmt::Write};
+use std::{borrow::Cow, fmt::Write};
 
-use crate::ast::*;
+use crate::{
+    ast::*,
+    source_map::SourceMap,
+    span::*,
+    token::{Token, TokenType},
+};
 
 use super::*;
 
-#[derive(Default)]
-struct TestPrinter {
-    buffer: String,
-}
+// Prints the tree as nested s-expressions, one top level statement per line.
+struct TestPrinter;
 
-impl ExpressionVisitor for TestPrinter {
-    fn visit_identifier(&mut self, identifier: &Identifier) {
-        write!(self.buffer, "{}", identifier.to_string()).unwrap();
+impl Visitor<String> for TestPrinter {
+    fn visit_identifier(&mut self, identifier: &Identifier) -> String {
+        identifier.to_string()
     }
-    
-    fn visit_literal(&mut self, literal: &Literal) {
+
+    fn visit_literal(&mut self, literal: &Literal) -> String {
         match literal {
-            Literal::Int(value) => write!(self.buffer, "{}", value).unwrap(),
-            Literal::Str(value) => write!(self.buffer, "\"{}\"", value).unwrap(),
-            Literal::Bool(value) => write!(self.buffer, "{}", value).unwrap(),
-            Literal::Float(value) => write!(self.buffer, "{}", value).unwrap(),
+            Literal::Int(value) => value.to_string(),
+            Literal::Str(value) => format!("\"{value}\""),
+            Literal::Bool(value) => value.to_string(),
+            Literal::Float(value) => value.to_string(),
+            Literal::Null => "null".to_string(),
         }
     }
 
-    fn visit_binary(&mut self, binary: &Binary) {
-        use BinaryOp::*;
-
-        write!(self.buffer, "(").unwrap();
-
-        match binary.op {
-            And => write!(self.buffer, "&& ").unwrap(),
-            Div => write!(self.buffer, "/ ").unwrap(),
-            Eq => write!(self.buffer, "== ").unwrap(),
-            Gt => write!(self.buffer, "> ").unwrap(),
-            Gte => write!(self.buffer, ">= ").unwrap(),
-            Lt => write!(self.buffer, "< ").unwrap(),
-            Lte => write!(self.buffer, "<= ").unwrap(),
-            Minus => write!(self.buffer, "- ").unwrap(),
-            Mod => write!(self.buffer, "% ").unwrap(),
-            Mult => write!(self.buffer, "* ").unwrap(),
-            Neq => write!(self.buffer, "!= ").unwrap(),
-            Or => write!(self.buffer, "|| ").unwrap(),
-            Plus => write!(self.buffer, "+ ").unwrap(),
-            Assign => write!(self.buffer, "= ").unwrap(),
-            AssignPlus => write!(self.buffer, "+= ").unwrap(),
-            AssignMinus => write!(self.buffer, "-= ").unwrap(),
-            AssignMult => write!(self.buffer, "*= ").unwrap(),
-            AssignDiv => write!(self.buffer, "/= ").unwrap(),
-            AssignMod => write!(self.buffer, "%= ").unwrap(),
-            AssignBitAnd => write!(self.buffer, "&= ").unwrap(),
-            AssignBitOr => write!(self.buffer, "|= ").unwrap(),
-            AssignBitXor => write!(self.buffer, "^= ").unwrap(),
-            AssignShiftLeft => write!(self.buffer, "<<= ").unwrap(),
-            AssignShiftRight => write!(self.buffer, ">>= ").unwrap(),
-            BitOr => write!(self.buffer, "| ").unwrap(),
-            BitAnd => write!(self.buffer, "& ").unwrap(),
-            BitXor => write!(self.buffer, "^ ").unwrap(),
-            ShiftLeft => write!(self.buffer, "<< ").unwrap(),
-            ShiftRight => write!(self.buffer, ">> ").unwrap(),
+    fn visit_binary(&mut self, binary: &Binary) -> String {
+        format!("({} {} {})", binary.op, self.visit_expression(binary.left.as_ref()), self.visit_expression(binary.right.as_ref()))
+    }
+
+    fn visit_unary(&mut self, unary: &Unary) -> String {
+        format!("({} {})", unary.op, self.visit_expression(unary.right.as_ref()))
+    }
+
+    fn visit_if(&mut self, if_expr: &If) -> String {
+        let mut output = format!(
+            "(if {})\n{}",
+            self.visit_expression(if_expr.condition.as_ref()),
+            self.visit_statement(if_expr.consequence.as_ref()),
+        );
+
+        if let Some(alternative) = &if_expr.alternative {
+            write!(output, "\n(else)\n{}\n", self.visit_statement(alternative.as_ref())).unwrap();
         }
 
-        binary.left.accept(self);
-        write!(self.buffer, " ").unwrap();
+        output + "(endif)"
+    }
+
+    fn visit_call(&mut self, call: &Call) -> String {
+        let mut output = format!("(call {}", self.visit_expression(call.callee.as_ref()));
 
-        binary.right.accept(self);
-        write!(self.buffer, ")").unwrap();
+        for argument in &call.arguments {
+            write!(output, " {}", self.visit_expression(argument.as_ref())).unwrap();
+        }
+
+        output + ")"
     }
 
-    fn visit_unary(&mut self, unary: &Unary) {
-        use UnaryOp::*;
+    fn visit_member(&mut self, member: &Member) -> String {
+        format!("(. {} {})", self.visit_expression(member.object.as_ref()), self.visit_expression(member.property.as_ref()))
+    }
 
-        write!(self.buffer, "(").unwrap();
+    fn visit_index(&mut self, index: &Index) -> String {
+        format!("([] {} {})", self.visit_expression(index.object.as_ref()), self.visit_expression(index.index.as_ref()))
+    }
 
-        match unary.op {
-            Minus => write!(self.buffer, "- ").unwrap(),
-            Not => write!(self.buffer, "! ").unwrap(),
-            BitNot => write!(self.buffer, "~ ").unwrap(),
-        }
+    fn visit_function_literal(&mut self, function: &FunctionLiteral) -> String {
+        format!("(fn ({}) {})", self.parameters(&function.parameters), self.visit_statement(&**function.body))
+    }
 
-        unary.right.accept(self);
-        write!(self.buffer, ")").unwrap();
+    fn visit_range(&mut self, range: &Range) -> String {
+        let op = if range.inclusive { "..=" } else { ".." };
+
+        format!("({op} {} {})", self.visit_expression(range.start.as_ref()), self.visit_expression(range.end.as_ref()))
     }
-    
-    fn visit_if(&mut self, if_expr: &If) {
-        write!(self.buffer, "(if ").unwrap();
-        if_expr.condition.accept(self);
-        write!(self.buffer, ")\n").unwrap();
-        if_expr.consequence.accept(self);
 
-        if let Some(alternarive) = &if_expr.alternative {
-            write!(self.buffer, "\n(else)\n").unwrap();
-            alternarive.accept(self);
-            write!(self.buffer, "\n").unwrap();
+    fn visit_fstring(&mut self, fstring: &FString) -> String {
+        let mut output = "(fstr".to_string();
+
+        for part in &fstring.parts {
+            match part {
+                FStringPart::Text(text) => write!(output, " \"{text}\"").unwrap(),
+                FStringPart::Expression(expression) => write!(output, " {}", self.visit_expression(expression.as_ref())).unwrap(),
+            }
         }
 
-        write!(self.buffer, "(endif)").unwrap();
+        output + ")"
     }
-}
 
-impl StatementVisitor for TestPrinter {
-    fn visit_ast(&mut self, ast: &Ast) {
-        for stmt in ast.statements() {
-            stmt.accept(self);
-            write!(self.buffer, "\n").unwrap();
+    fn visit_ast(&mut self, ast: &Ast) -> String {
+        ast.statements().iter()
+            .map(|statement| self.visit_statement(statement.as_ref()) + "\n")
+            .collect()
+    }
+
+    fn visit_let(&mut self, let_statement: &Let) -> String {
+        let mut output = format!("(let {}", self.visit_expression(let_statement.identifier.as_ref()));
+
+        if let Some(expression) = &let_statement.expression {
+            write!(output, " = {}", self.visit_expression(expression.as_ref())).unwrap();
         }
+
+        output + ")"
     }
 
-    fn visit_let(&mut self, let_expr: &Let) {
-        write!(self.buffer, "(let ").unwrap();
-        let_expr.identifier.accept(self);
-        if let Some(expr) = &let_expr.expression {
-            write!(self.buffer, " = ").unwrap();
-            expr.accept(self);
+    fn visit_const(&mut self, const_statement: &Const) -> String {
+        format!(
+            "(const {} = {})",
+            self.visit_expression(const_statement.identifier.as_ref()),
+            self.visit_expression(const_statement.expression.as_ref()),
+        )
+    }
+
+    fn visit_return(&mut self, return_statement: &Return) -> String {
+        match &return_statement.expression {
+            Some(expression) => format!("(return {})", self.visit_expression(expression.as_ref())),
+            None => "(return)".to_string(),
         }
-        write!(self.buffer, ")").unwrap();
     }
-    
-    fn visit_return(&mut self, return_statement: &Return) {
-        write!(self.buffer, "(return").unwrap();
 
-        if let Some(expr) = &return_statement.expression {
-            write!(self.buffer, " ").unwrap();
-            expr.accept(self);
+    fn visit_expr(&mut self, expr: &Expr) -> String {
+        self.visit_expression(expr.expression.as_ref())
+    }
+
+    fn visit_block(&mut self, block: &Block) -> String {
+        let mut output = "(block)\n".to_string();
+
+        for statement in &block.statements {
+            writeln!(output, "{}", self.visit_statement(statement.as_ref())).unwrap();
         }
 
-        write!(self.buffer, ")").unwrap();
+        output + "(end block)"
     }
-    
-    fn visit_expr(&mut self, expr: &Expr) {
-        expr.expression.accept(self);
+
+    fn visit_function(&mut self, function: &Function) -> String {
+        format!(
+            "(fn {} ({}) {})",
+            self.visit_expression(function.name.as_ref()),
+            self.parameters(&function.parameters),
+            self.visit_statement(&**function.body),
+        )
     }
-    
-    fn visit_block(&mut self, block: &Block) {
-        write!(self.buffer, "(block)\n").unwrap();
-        for statement in &block.statements {
-            statement.accept(self);
-            write!(self.buffer, "\n").unwrap();
+
+    fn visit_while(&mut self, while_statement: &While) -> String {
+        format!(
+            "(while {})\n{}\n(endwhile)",
+            self.visit_expression(while_statement.condition.as_ref()),
+            self.visit_statement(while_statement.body.as_ref()),
+        )
+    }
+
+    fn visit_for(&mut self, for_statement: &For) -> String {
+        let initializer = for_statement.initializer.as_ref()
+            .map(|initializer| self.visit_statement(initializer.as_ref()))
+            .unwrap_or_default();
+        let condition = for_statement.condition.as_ref()
+            .map(|condition| self.visit_expression(condition.as_ref()))
+            .unwrap_or_default();
+        let update = for_statement.update.as_ref()
+            .map(|update| self.visit_expression(update.as_ref()))
+            .unwrap_or_default();
+
+        format!("(for {initializer}; {condition}; {update})\n{}\n(endfor)", self.visit_statement(for_statement.body.as_ref()))
+    }
+
+    fn visit_for_in(&mut self, for_in: &ForIn) -> String {
+        format!(
+            "(for {} in {})\n{}\n(endfor)",
+            self.visit_expression(for_in.variable.as_ref()),
+            self.visit_expression(for_in.iterable.as_ref()),
+            self.visit_statement(for_in.body.as_ref()),
+        )
+    }
+
+    fn visit_break(&mut self, _break_statement: &Break) -> String {
+        "(break)".to_string()
+    }
+
+    fn visit_continue(&mut self, _continue_statement: &Continue) -> String {
+        "(continue)".to_string()
+    }
+
+    fn visit_error_statement(&mut self, _error_statement: &ErrorStatement) -> String {
+        "(error)".to_string()
+    }
+
+    fn visit_class(&mut self, class: &Class) -> String {
+        let mut output = format!("(class {}", self.visit_expression(class.name.as_ref()));
+
+        if let Some(superclass) = &class.superclass {
+            write!(output, " extends {}", self.visit_expression(superclass.as_ref())).unwrap();
+        }
+
+        output += ")\n";
+
+        for member in &class.members {
+            match member {
+                ClassMember::Method(method) => {
+                    let kind = match method.kind {
+                        MethodKind::Constructor => "constructor",
+                        MethodKind::Method => "method",
+                        MethodKind::Getter => "get",
+                        MethodKind::Setter => "set",
+                    };
+                    let modifier = if method.is_static { "static " } else { "" };
+
+                    writeln!(
+                        output,
+                        "({modifier}{kind} {} ({}) {})",
+                        self.visit_expression(method.name.as_ref()),
+                        self.parameters(&method.parameters),
+                        self.visit_statement(&**method.body),
+                    ).unwrap();
+                }
+                ClassMember::Property(property) => {
+                    write!(output, "(property {}", self.visit_expression(property.name.as_ref())).unwrap();
+
+                    if let Some(value) = property.value.as_deref() {
+                        write!(output, " = {}", self.visit_expression(value.as_ref())).unwrap();
+                    }
+
+                    writeln!(output, ")").unwrap();
+                }
+            }
         }
-        write!(self.buffer, "(end block)").unwrap();
+
+        output + "(endclass)"
+    }
+}
+
+impl TestPrinter {
+    fn parameters(&mut self, parameters: &[Box<dyn Expression>]) -> String {
+        parameters.iter()
+            .map(|parameter| self.visit_expression(parameter.as_ref()))
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 }
 
@@ -146,23 +245,28 @@ fn test_parse_let_statement() {
     let test_cases = vec![
         ("let a = 5;", "(let a = 5)\n"),
         ("let name = \"orc\\u{2764}\";", "(let name = \"orc❤\")\n"),
+        ("let s = \"\\x4a\\u{1F600}\";", "(let s = \"J😀\")\n"),
         ("let alive = true;", "(let alive = true)\n"),
         ("let z = x + y;", "(let z = (+ x y))\n"),
         ("let dmg = (a + b * c) / 2 - crit;", "(let dmg = (- (/ (+ a (* b c)) 2) crit))\n"),
         ("let neg = -100.5", "(let neg = (- 100.5))\n"),
-//         ("let msg = f\"HP: {hp}\";", "(let msg = (fstr \"HP: {hp}\"))\n"),
+        ("let nothing = null;", "(let nothing = null)\n"),
+        ("let name = 'orc';", "(let name = \"orc\")\n"),
+        (r#"let quote = 'it\'s "fine"' + "it\'s";"#, "(let quote = (+ \"it's \"fine\"\" \"it's\"))\n"),
+        ("let same = a == null || f(null);", "(let same = (|| (== a null) (call f null)))\n"),
+        ("let msg = f\"HP: {hp}\";", "(let msg = (fstr \"HP: {hp}\"))\n"),
 //         ("let arr = [1, 2, 3, 4];", "(let arr = ([1, 2, 3, 4]))\n"),
 //         ("let obj = { x: 1, y: 2 };", "(let obj = (obj {x: (1), y: (2)}))\n"),
 //         ("let player = new Player();", "TODO"),
-//         ("let hp = player.hp;", "TODO"),
-//         ("let name = player.getName();", "TODO"),
-//         ("let val = obj.key.subkey.method();", "TODO"),
+        ("let hp = player.hp;", "(let hp = (. player hp))\n"),
+        ("let name = player.getName();", "(let name = (call (. player getName)))\n"),
+        ("let val = obj.key.subkey.method();", "(let val = (call (. (. (. obj key) subkey) method)))\n"),
 //         ("let data = await loadData();", "TODO"),
 //         ("let token = await user.getToken();", "TODO"),
-//         ("let result = compute(1, 2, 3);", "TODO"),
-//         ("let status = check(player.hp, 10);", "TODO"),
-//         ("let ok = a > 10;", "TODO"),
-//         ("let fail = a == b && c != d;", "TODO"),
+        ("let result = compute(1, 2, 3);", "(let result = (call compute 1 2 3))\n"),
+        ("let status = check(player.hp, 10);", "(let status = (call check (. player hp) 10))\n"),
+        ("let ok = a > 10;", "(let ok = (> a 10))\n"),
+        ("let fail = a == b && c != d;", "(let fail = (&& (== a b) (!= c d)))\n"),
 //         (r#"
 // if (x > 10) {
 //     let y = x * 2;
@@ -185,10 +289,7 @@ fn test_parse_let_statement() {
         let lexer = Lexer::new(tc.0.to_string());
         let parser = Parser::new(lexer);
         let ast = parser.parse().unwrap();
-        let mut test_printer = TestPrinter::default();
-        test_printer.visit_ast(&ast);
-
-        assert_eq!(tc.1, test_printer.buffer);
+        assert_eq!(tc.1, TestPrinter.visit_ast(&ast));
     }
 }
 
@@ -200,7 +301,7 @@ fn test_parse_return_statement() {
         ("return 123;", "(return 123)\n"),
         ("return a + b", "(return (+ a b))\n"),
         ("return -42", "(return (- 42))\n"),
-        // ("return player.hp", "TODO\n"),
+        ("return player.hp", "(return (. player hp))\n"),
         (
             r#"
 return (
@@ -223,14 +324,14 @@ x; // unreachable
 "#,
             "(return)\nx\n",
         ),
-//         (
-//             r#"
-// fn f() {
-//   return 10
-// }
-// "#,
-//             "TODO",
-//         ),
+        (
+            r#"
+fn f() {
+  return 10
+}
+"#,
+            "(fn f () (block)\n(return 10)\n(end block))\n",
+        ),
 //         (
 //             r#"
 // if (x > 0)
@@ -248,14 +349,14 @@ x; // unreachable
 // "#,
 //             "TODO",
 //         ),
-//         (
-//             r#"
-// while (true) {
-//   return x
-// }
-// "#,
-//             "TODO",
-//         ),
+        (
+            r#"
+while (true) {
+  return x
+}
+"#,
+            "(while true)\n(block)\n(return x)\n(end block)\n(endwhile)\n",
+        ),
 //         (
 //             r#"
 // return {
@@ -266,17 +367,14 @@ x; // unreachable
 //             "TODO",
 //         ),
         // ("return [1, 2, 3]", "TODO"),
-        // ("return (x) => x * 2", "TODO"),
+        ("return (x) => x * 2", "(return (fn (x) (return (* x 2))))\n"),
     ];
 
     for tc in test_cases {
         let lexer = Lexer::new(tc.0.to_string());
         let parser = Parser::new(lexer);
         let ast = parser.parse().unwrap();
-        let mut test_printer = TestPrinter::default();
-        test_printer.visit_ast(&ast);
-
-        assert_eq!(tc.1, test_printer.buffer);
+        assert_eq!(tc.1, TestPrinter.visit_ast(&ast));
     }
 }
 
@@ -287,10 +385,16 @@ fn test_parse_expression_statement() {
         ("x", "x\n"),
         ("\"hello\"", "\"hello\"\n"),
         ("1.3e-1", "0.13\n"),
-        // ("true != false", "(!= true false)\n"),
-        ("a + b - c", "(+ a (- b c))\n"),
-        ("a * b / c % d", "(* a (/ b (% c d)))\n"),
-        ("-a + !b - ~c", "(+ (- a) (- (! b) (~ c)))\n"),
+        ("true != false", "(!= true false)\n"),
+        ("a + b - c", "(- (+ a b) c)\n"),
+        ("a * b / c % d", "(% (/ (* a b) c) d)\n"),
+        ("-a + !b - ~c", "(- (+ (- a) (! b)) (~ c))\n"),
+        ("10 - 3 - 2", "(- (- 10 3) 2)\n"),
+        ("a / b / c", "(/ (/ a b) c)\n"),
+        ("a << b >> c", "(>> (<< a b) c)\n"),
+        ("a & b & c", "(& (& a b) c)\n"),
+        ("a && b && c", "(&& (&& a b) c)\n"),
+        ("a + b * c - d", "(- (+ a (* b c)) d)\n"),
         ("(a + b) * d", "(* (+ a b) d)\n"),
         (
             r#"
@@ -319,18 +423,27 @@ fn test_parse_expression_statement() {
         ("a &= b", "(&= a b)\n"),
         ("a >>= b", "(>>= a b)\n"),
         ("a <<= b", "(<<= a b)\n"),
+        ("a = b = c", "(= a (= b c))\n"),
+        ("a += b -= c", "(+= a (-= b c))\n"),
+        ("a = b + c - d", "(= a (- (+ b c) d))\n"),
         // ("[1, 2, 3]", "TODO"),
-        // ("foo()", "TODO"),
-        // ("foo(1, 2, x)", "TODO"),
-        // ("foo(bar(1))", "TODO"),
-        // ("player.move(10)", "TODO"),
-        // ("foo().bar().baz()", "TODO"),
-        // ("x = 10", "TODO"),
-        // ("player.hp = 100", "TODO"),
-        // ("x = y + z * 3", "TODO"),
-        // ("config = loadConfig()", "TODO"),
-        // ("foo(1) + bar(2)", "TODO"),
-        // ("flag || doStuff()", "TODO"),
+        ("foo()", "(call foo)\n"),
+        ("foo(1, 2, x)", "(call foo 1 2 x)\n"),
+        ("foo(bar(1))", "(call foo (call bar 1))\n"),
+        ("player.move(10)", "(call (. player move) 10)\n"),
+        ("foo().bar().baz()", "(call (. (call (. (call foo) bar)) baz))\n"),
+        ("x = 10", "(= x 10)\n"),
+        ("player.hp = 100", "(= (. player hp) 100)\n"),
+        ("x = y + z * 3", "(= x (+ y (* z 3)))\n"),
+        ("config = loadConfig()", "(= config (call loadConfig))\n"),
+        ("foo(1) + bar(2)", "(+ (call foo 1) (call bar 2))\n"),
+        ("flag || doStuff()", "(|| flag (call doStuff))\n"),
+        ("items[0]", "([] items 0)\n"),
+        ("items[i + 1][j]", "([] ([] items (+ i 1)) j)\n"),
+        ("f(a, b).x[i](y)", "(call ([] (. (call f a b) x) i) y)\n"),
+        ("-a.b(c)", "(- (call (. a b) c))\n"),
+        ("(a + b).c", "(. (+ a b) c)\n"),
+        ("items[0] = f(1)[2]", "(= ([] items 0) ([] (call f 1) 2))\n"),
         ("!ready", "(! ready)\n"),
         ("!!ready", "(! (! ready))\n"),
         ("-count", "(- count)\n"),
@@ -351,23 +464,20 @@ fn test_parse_expression_statement() {
 // }"#,
 //             "TODO",
 //         ),
-//         (
-//             r#"
-// foo();
-// bar();
-// "#,
-//             "TODO",
-//         ),
+        (
+            r#"
+foo();
+bar();
+"#,
+            "(call foo)\n(call bar)\n",
+        ),
     ];
 
     for tc in test_cases {
         let lexer = Lexer::new(tc.0.to_string());
         let parser = Parser::new(lexer);
         let ast = parser.parse().unwrap();
-        let mut test_printer = TestPrinter::default();
-        test_printer.visit_ast(&ast);
-
-        assert_eq!(tc.1, test_printer.buffer);
+        assert_eq!(tc.1, TestPrinter.visit_ast(&ast));
     }
 }
 
@@ -403,10 +513,7 @@ fn test_parse_block() {
         let lexer = Lexer::new(tc.0.to_string());
         let parser = Parser::new(lexer);
         let ast = parser.parse().unwrap();
-        let mut test_printer = TestPrinter::default();
-        test_printer.visit_ast(&ast);
-
-        assert_eq!(tc.1, test_printer.buffer);
+        assert_eq!(tc.1, TestPrinter.visit_ast(&ast));
     }
 }
 
@@ -460,9 +567,859 @@ false
         let lexer = Lexer::new(tc.0.to_string());
         let parser = Parser::new(lexer);
         let ast = parser.parse().unwrap();
-        let mut test_printer = TestPrinter::default();
-        test_printer.visit_ast(&ast);
+        assert_eq!(tc.1, TestPrinter.visit_ast(&ast));
+    }
+}
+
+// Binary productions of the `ebnf` grammar from the lowest to the highest precedence.
+const EBNF_BINARY_PRODUCTIONS: [(&str, &[&str]); 11] = [
+    ("range", &["..", "..="]),
+    ("logical_or", &["||"]),
+    ("logical_and", &["&&"]),
+    ("comparison", &["<", "<=", ">", ">="]),
+    ("equality", &["==", "!="]),
+    ("bit_or", &["|"]),
+    ("bit_xor", &["^"]),
+    ("bit_and", &["&"]),
+    ("shift", &["<<", ">>", ">>>"]),
+    ("additive", &["+", "-"]),
+    ("multiplicative", &["*", "/", "%"]),
+];
+
+const EBNF_ASSIGNMENT_OPS: [&str; 12] = ["=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=", ">>>="];
+
+fn parse_to_string(input: &str) -> String {
+    let lexer = Lexer::new(input.to_string());
+    let parser = Parser::new(lexer);
+    let ast = parser.parse().unwrap_or_else(|err| panic!("{input}: {err}"));
+    TestPrinter.visit_ast(&ast)
+}
+
+#[test]
+fn test_ebnf_binary_productions_are_left_associative() {
+    for (production, ops) in EBNF_BINARY_PRODUCTIONS {
+        for first in ops {
+            for second in ops {
+                let input = format!("a {first} b {second} c");
+                let expected = format!("({second} ({first} a b) c)\n");
+
+                assert_eq!(expected, parse_to_string(&input), "{production}: {input}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ebnf_binary_productions_precedence() {
+    for (i, (lower, lower_ops)) in EBNF_BINARY_PRODUCTIONS.iter().enumerate() {
+        for (higher, higher_ops) in &EBNF_BINARY_PRODUCTIONS[i + 1..] {
+            for lo in lower_ops.iter() {
+                for hi in higher_ops.iter() {
+                    let input = format!("a {lo} b {hi} c");
+                    let expected = format!("({lo} a ({hi} b c))\n");
+                    assert_eq!(expected, parse_to_string(&input), "{lower} < {higher}: {input}");
+
+                    let input = format!("a {hi} b {lo} c");
+                    let expected = format!("({lo} ({hi} a b) c)\n");
+                    assert_eq!(expected, parse_to_string(&input), "{lower} < {higher}: {input}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ebnf_assignment_production() {
+    for op in EBNF_ASSIGNMENT_OPS {
+        for other in EBNF_ASSIGNMENT_OPS {
+            let input = format!("a {op} b {other} c");
+            let expected = format!("({op} a ({other} b c))\n");
+            assert_eq!(expected, parse_to_string(&input), "{input}");
+        }
+
+        for (production, ops) in EBNF_BINARY_PRODUCTIONS {
+            for binary in ops {
+                let input = format!("a {op} b {binary} c");
+                let expected = format!("({op} a ({binary} b c))\n");
+                assert_eq!(expected, parse_to_string(&input), "{production}: {input}");
+
+                let input = format!("a {binary} b {op} c");
+                let expected = format!("({op} ({binary} a b) c)\n");
+                assert_eq!(expected, parse_to_string(&input), "{production}: {input}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ebnf_unary_production() {
+    for unary in ["!", "~", "-"] {
+        assert_eq!(format!("({unary} ({unary} a))\n"), parse_to_string(&format!("{unary}{unary}a")));
+
+        for (production, ops) in EBNF_BINARY_PRODUCTIONS {
+            for binary in ops {
+                let input = format!("{unary}a {binary} {unary}b");
+                let expected = format!("({binary} ({unary} a) ({unary} b))\n");
+                assert_eq!(expected, parse_to_string(&input), "{production}: {input}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ebnf_primary_production() {
+    let test_cases = vec![
+        ("42", "42\n"),
+        ("4.2", "4.2\n"),
+        ("\"nya\"", "\"nya\"\n"),
+        ("true", "true\n"),
+        ("false", "false\n"),
+        ("null", "null\n"),
+        ("nya", "nya\n"),
+        ("(a || b) && c", "(&& (|| a b) c)\n"),
+        ("a * (b + c)", "(* a (+ b c))\n"),
+        ("((a))", "a\n"),
+        ("a /* (b) */ + /* /* nested */ */ c", "(+ a c)\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_ebnf_statement_productions() {
+    let test_cases = vec![
+        // program = { statement }
+        ("", ""),
+        ("a; b", "a\nb\n"),
+        // block = "{" { statement } "}"
+        ("{ a; b }", "(block)\na\nb\n(end block)\n"),
+        // let_statement = "let" identifier ( "=" expression )? semicolon
+        ("let a", "(let a)\n"),
+        ("let a = b || c", "(let a = (|| b c))\n"),
+        // return_statement = "return" expression? semicolon
+        ("return a || b;", "(return (|| a b))\n"),
+        // if_statement = "if" "(" expression ")" statement ( "else" statement )?
+        ("if (a || b) c", "(if (|| a b))\nc(endif)\n"),
+        ("if (a) b; else c", "(if a)\nb\n(else)\nc\n(endif)\n"),
+        // expression_statement = expression semicolon
+        ("a || b = c;", "(= (|| a b) c)\n"),
+        ("a = b || c;", "(= a (|| b c))\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_function() {
+    let test_cases = vec![
+        ("fn f() {}", "(fn f () (block)\n(end block))\n"),
+        ("fn add(a, b) { return a + b; }", "(fn add (a b) (block)\n(return (+ a b))\n(end block))\n"),
+        ("fn f() {} f()", "(fn f () (block)\n(end block))\n(call f)\n"),
+        ("let f = fn (x) { x * 2 };", "(let f = (fn (x) (block)\n(* x 2)\n(end block)))\n"),
+        ("map(items, fn (item) { item.id })", "(call map items (fn (item) (block)\n(. item id)\n(end block)))\n"),
+        ("fn () {}()", "(call (fn () (block)\n(end block)))\n"),
+        ("let f = () => 1;", "(let f = (fn () (return 1)))\n"),
+        ("let add = (a, b) => { return a + b; };", "(let add = (fn (a b) (block)\n(return (+ a b))\n(end block)))\n"),
+        ("map(items, (item) => item.id, 1)", "(call map items (fn (item) (return (. item id))) 1)\n"),
+        ("(a) + (b)", "(+ a b)\n"),
+        (
+            r#"
+fn outer(a) {
+    fn inner(b) {
+        return a + b;
+    }
+    return inner;
+}"#,
+            r#"(fn outer (a) (block)
+(fn inner (b) (block)
+(return (+ a b))
+(end block))
+(return inner)
+(end block))
+"#
+        ),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_function_errors() {
+    let test_cases = vec![
+        "fn f {}",
+        "fn f(a b) {}",
+        "fn f(1) {}",
+        "fn f() return 1;",
+        "fn (a,) {}",
+        "(a, b)",
+        "(a, 1) => a",
+        "(a) => ",
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.to_string());
+
+        assert!(Parser::new(lexer).parse().is_err(), "{tc}");
+    }
+}
+
+#[test]
+fn test_parse_loops() {
+    let test_cases = vec![
+        ("while (a) b;", "(while a)\nb\n(endwhile)\n"),
+        ("while (a < 10) { a += 1; }", "(while (< a 10))\n(block)\n(+= a 1)\n(end block)\n(endwhile)\n"),
+        (
+            "for (let i = 0; i < 10; i += 1) { f(i); }",
+            "(for (let i = 0); (< i 10); (+= i 1))\n(block)\n(call f i)\n(end block)\n(endfor)\n",
+        ),
+        ("for (i = 0; i; i = i - 1) {}", "(for (= i 0); i; (= i (- i 1)))\n(block)\n(end block)\n(endfor)\n"),
+        ("for (;;) {}", "(for ; ; )\n(block)\n(end block)\n(endfor)\n"),
+        ("for (; a;) b;", "(for ; a; )\nb\n(endfor)\n"),
+        ("while (true) { break; }", "(while true)\n(block)\n(break)\n(end block)\n(endwhile)\n"),
+        ("while (true) { continue }", "(while true)\n(block)\n(continue)\n(end block)\n(endwhile)\n"),
+        ("while (a) if (b) break; else continue;", "(while a)\n(if b)\n(break)\n(else)\n(continue)\n(endif)\n(endwhile)\n"),
+        ("for (;;) while (a) break;", "(for ; ; )\n(while a)\n(break)\n(endwhile)\n(endfor)\n"),
+        ("while (a) {} b", "(while a)\n(block)\n(end block)\n(endwhile)\nb\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_range_and_for_in() {
+    let test_cases = vec![
+        ("0..10", "(.. 0 10)\n"),
+        ("a..=b", "(..= a b)\n"),
+        ("0..n - 1", "(.. 0 (- n 1))\n"),
+        ("let r = a + 1..b * 2;", "(let r = (.. (+ a 1) (* b 2)))\n"),
+        ("r = 0..len(s)", "(= r (.. 0 (call len s)))\n"),
+        ("for (let i in 0..10) f(i);", "(for i in (.. 0 10))\n(call f i)\n(endfor)\n"),
+        ("for (let ch in name) { break; }", "(for ch in name)\n(block)\n(break)\n(end block)\n(endfor)\n"),
+        ("for (let i in 0..=n) {}", "(for i in (..= 0 n))\n(block)\n(end block)\n(endfor)\n"),
+        ("for (let i = 0; i < 3; i += 1) {}", "(for (let i = 0); (< i 3); (+= i 1))\n(block)\n(end block)\n(endfor)\n"),
+        ("for (let i; i; i) {}", "(for (let i); i; i)\n(block)\n(end block)\n(endfor)\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+
+    for input in ["for (let 1 in a) {}", "for (let i in) {}", "for (i in a) {}", "for (let i in a {}"] {
+        let lexer = Lexer::new(input.to_string());
+
+        assert!(Parser::new(lexer).parse().is_err(), "{input}");
+    }
+}
+
+#[test]
+fn test_parse_loop_control_outside_loop() {
+    let test_cases = vec![
+        ("break;", 0, "break"),
+        ("continue", 0, "continue"),
+        ("if (a) { break; }", 9, "break"),
+        ("while (a) {}\ncontinue;", 13, "continue"),
+        ("while (a) { fn f() { break; } }", 21, "break"),
+        ("for (;;) { let f = fn () { continue; }; }", 27, "continue"),
+        ("for (let i in a) {}\nbreak", 20, "break"),
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let err = Parser::new(lexer).parse().unwrap_err();
+
+        match err.kind {
+            error::ErrorKind::OutsideLoop(token) => assert_eq!(tc.2, token.lexeme, "{}", tc.0),
+            kind => panic!("{}: unexpected error {kind:?}", tc.0),
+        }
+
+        assert_eq!(Some(Span::new(tc.1, tc.2.len())), err.span, "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_class() {
+    let test_cases = vec![
+        ("class A {}", "(class A)
+(endclass)
+"),
+        ("class B extends A {}", "(class B extends A)
+(endclass)
+"),
+        (
+            r#"
+class Point extends Shape {
+    x = 0;
+    y;
+    constructor(x, y) { this.x = x; }
+    static origin() { return Point(0, 0); }
+    get length() { return this.x; }
+    set length(value) { this.x = value; }
+    static get count() { return 1; }
+    move(dx) { this.x += dx; }
+}"#,
+            r#"(class Point extends Shape)
+(property x = 0)
+(property y)
+(constructor constructor (x y) (block)
+(= (. this x) x)
+(end block))
+(static method origin () (block)
+(return (call Point 0 0))
+(end block))
+(get length () (block)
+(return (. this x))
+(end block))
+(set length (value) (block)
+(= (. this x) value)
+(end block))
+(static get count () (block)
+(return 1)
+(end block))
+(method move (dx) (block)
+(+= (. this x) dx)
+(end block))
+(endclass)
+"#
+        ),
+        // Contextual keywords still work as member names.
+        (
+            "class M { get() {} set = 1; static() {} static static() {} constructor }",
+            "(class M)
+(method get () (block)
+(end block))
+(property set = 1)
+(method static () (block)
+(end block))
+\
+(static method static () (block)
+(end block))
+(property constructor)
+(endclass)
+",
+        ),
+        ("map.get(key).set", "(. (call (. map get) key) set)
+"),
+        ("class A {} A()", "(class A)
+(endclass)
+(call A)
+"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+
+    let error_cases = vec![
+        "class {}",
+        "class A extends {}",
+        "class A extends B, C {}",
+        "class A { static x = 1; }",
+        "class A { get x {} }",
+        "class A { f() }",
+        "class A { 1 }",
+        "class A {",
+        "class A { f() { break; } }",
+    ];
+
+    for tc in error_cases {
+        let lexer = Lexer::new(tc.to_string());
+
+        assert!(Parser::new(lexer).parse().is_err(), "{tc}");
+    }
+}
+
+#[test]
+fn test_parse_int_literals() {
+    let test_cases = vec![
+        ("0", "0\n"),
+        ("007", "7\n"),
+        ("1_000_000", "1000000\n"),
+        ("0xFF", "255\n"),
+        ("0xdead_BEEF", "3735928559\n"),
+        ("0b1010", "10\n"),
+        ("0b1111_0000", "240\n"),
+        ("0o17", "15\n"),
+        ("0o7_7", "63\n"),
+        ("9223372036854775807", "9223372036854775807\n"),
+        ("0x7fff_ffff_ffff_ffff", "9223372036854775807\n"),
+        ("-0x10 + 0b1", "(+ (- 16) 1)\n"),
+        ("-9223372036854775807", "(- 9223372036854775807)\n"),
+        ("-9223372036854775808", "-9223372036854775808\n"),
+        ("--9223372036854775808", "(- -9223372036854775808)\n"),
+        ("-0x8000_0000_0000_0000", "-9223372036854775808\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+
+    let error_cases = vec![
+        ("0x", Some(error::MalformedIntError::MissingDigits), 0, 2),
+        ("a + 0b", Some(error::MalformedIntError::MissingDigits), 4, 2),
+        ("1__0", Some(error::MalformedIntError::ConsecutiveSeparators), 0, 4),
+        ("let a =\n  10_;", Some(error::MalformedIntError::TrailingSeparator), 10, 3),
+        ("0x_ff", Some(error::MalformedIntError::LeadingSeparator), 0, 5),
+        ("0b_", Some(error::MalformedIntError::LeadingSeparator), 0, 3),
+        ("0b12", Some(error::MalformedIntError::InvalidDigit('2')), 0, 4),
+        ("let a = 0o178;", Some(error::MalformedIntError::InvalidDigit('8')), 8, 5),
+        ("0xffg", Some(error::MalformedIntError::InvalidDigit('g')), 0, 5),
+        ("9223372036854775808", None, 0, 19),
+        ("f(1, 0x1_0000_0000_0000_0000)", None, 5, 23),
+        ("0b1000000000000000000000000000000000000000000000000000000000000000", None, 0, 66),
+        ("-9223372036854775809", None, 1, 19),
+        ("-9223372036854775808.f", None, 1, 19),
+        ("-(9223372036854775808)", None, 2, 19),
+        ("1 - 9223372036854775808", None, 4, 19),
+    ];
+
+    for tc in error_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let err = Parser::new(lexer).parse().unwrap_err();
+
+        match (err.kind, tc.1) {
+            (error::ErrorKind::MalformedInt(err), Some(expected)) => assert_eq!(expected, err, "{}", tc.0),
+            (error::ErrorKind::IntOverflow, None) => {},
+            (kind, _) => panic!("{}: unexpected error {kind:?}", tc.0),
+        }
+
+        assert_eq!(Some(Span::new(tc.2, tc.3)), err.span, "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_string_escape_errors() {
+    use error::ParseStringError::*;
+
+    let test_cases = vec![
+        ("\"\\xz1\"", UnexpectedChar('z')),
+        ("\"\\x4\"", UnexpectedEnding),
+        ("\"\\u{}\"", UnexpectedChar('}')),
+        ("\"\\u{12g}\"", UnexpectedChar('g')),
+        ("\"\\u{1234567}\"", UnexpectedChar('7')),
+        ("\"\\u{d800}\"", UndefinedUnicode(0xd800)),
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let err = Parser::new(lexer).parse().unwrap_err();
+
+        match err.kind {
+            error::ErrorKind::ParseString(err) => assert_eq!(tc.1, err, "{}", tc.0),
+            kind => panic!("{}: unexpected error {kind:?}", tc.0),
+        }
+
+        assert_eq!(Some(Span::new(0, tc.0.len())), err.span, "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_fstring() {
+    let test_cases = vec![
+        ("f\"\"", "(fstr)\n"),
+        ("f\"Hello ${name}!\"", "(fstr \"Hello \" name \"!\")\n"),
+        ("f\"${a}${b}\"", "(fstr a b)\n"),
+        ("f\"${ a + b * 2 } = ${f(x)}\"", "(fstr (+ a (* b 2)) \" = \" (call f x))\n"),
+        ("f\"\\${a} \\\"${b}\\\"\"", "(fstr \"${a} \"\" b \"\"\")\n"),
+        ("f\"${ if (a) { \"}\" } else { 'b' } }!\"", "(fstr (if a)\n(block)\n\"}\"\n(end block)\n(else)\n(block)\n\"b\"\n(end block)\n(endif) \"!\")\n"),
+        ("f\"a ${f\"b ${c}\"} d\"", "(fstr \"a \" (fstr \"b \" c) \" d\")\n"),
+        ("let s = f\"${n}\" + \"!\";", "(let s = (+ (fstr n) \"!\"))\n"),
+        ("f", "f\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+
+    // Errors inside `${...}` point into the f-string, not at its start.
+    let error_cases = vec![
+        ("f\"abc ${1 +} d\"", 11),
+        ("f\"abc ${}\"", 8),
+        ("let s = f\"x\n${a b}\";", 16),
+        ("f\"${f\"${)}\"}\"", 8),
+        ("f\"abc ${a\"", 9),
+        ("f\"abc", 2),
+    ];
+
+    for tc in error_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let err = Parser::new(lexer).parse().unwrap_err();
+
+        assert_eq!(Some(tc.1), err.span.map(|s| s.start), "{}: {err}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_fragments() {
+    let expression = Parser::parse_expression("a.b + f(1) * 2").unwrap();
+    assert_eq!("(+ (. a b) (* (call f 1) 2))", TestPrinter.visit_expression(expression.as_ref()));
+
+    let statement = Parser::parse_statement("let x = (y) => y;").unwrap();
+    assert_eq!("(let x = (fn (y) (return y)))", TestPrinter.visit_statement(statement.as_ref()));
+
+    let block = Parser::parse_block("{ const a = 1; a }").unwrap();
+    assert_eq!("(block)\n(const a = 1)\na\n(end block)", TestPrinter.visit_statement(block.as_ref()));
+
+    // Trailing tokens are an error, pointing at the first one left over.
+    let error_cases = vec![
+        (Parser::parse_expression("1 + 2;").map(|_| ()), 5),
+        (Parser::parse_expression("a b").map(|_| ()), 2),
+        (Parser::parse_expression("").map(|_| ()), 0),
+        (Parser::parse_statement("let a = 1; let b = 2;").map(|_| ()), 11),
+        (Parser::parse_statement("a }").map(|_| ()), 2),
+        (Parser::parse_block("{ a } b").map(|_| ()), 6),
+        (Parser::parse_block("a").map(|_| ()), 0),
+        (Parser::parse_block("{ const a = 1; a = 2; }").map(|_| ()), 15),
+        (Parser::parse_expression("fn () { const a = 1; a += 1; }").map(|_| ()), 21),
+    ];
+
+    for (index, (result, position)) in error_cases.into_iter().enumerate() {
+        let err = result.unwrap_err();
+
+        assert_eq!(Some(position), err.span.map(|s| s.start), "case {index}: {err}");
+    }
+
+    let err = Parser::parse_expression("1 + 2;").unwrap_err();
+    assert!(matches!(err.kind, error::ErrorKind::UnexpectedToken(_)), "{err}");
+}
+
+#[test]
+fn test_parse_recovering() {
+    let test_cases = vec![
+        ("let a = 1;", "(let a = 1)\n", vec![]),
+        ("let a = ; let b = 2;", "(error)\n(let b = 2)\n", vec![8]),
+        ("let a = @1; b = ;\nc", "(let a = 1)\n(error)\nc\n", vec![8, 16]),
+        (
+            "fn f() { let = 1; return 2; } }",
+            "(fn f () (block)\n(error)\n(return 2)\n(end block))\n(error)\n",
+            vec![13, 30],
+        ),
+        ("if (x { a; } b; c;", "(error)\nc\n", vec![6]),
+        ("{ 1 + }", "(block)\n(error)\n(end block)\n", vec![6]),
+        ("while (true) { break; } break;", "(while true)\n(block)\n(break)\n(end block)\n(endwhile)\n(error)\n", vec![24]),
+        ("const a = 1; a = 2; let = 3;", "(const a = 1)\n(= a 2)\n(error)\n", vec![13, 24]),
+        ("const a = 1; a = 2; a = 3; let x = ;", "(const a = 1)\n(= a 2)\n(= a 3)\n(error)\n", vec![13, 20, 35]),
+        ("let s = \"abc", "(error)\n", vec![8, 12]),
+        ("fn f() { a;", "(error)\n", vec![11]),
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let (ast, errors) = Parser::new(lexer).parse_recovering();
+        assert_eq!(tc.1, TestPrinter.visit_ast(&ast), "{}", tc.0);
+        assert_eq!(tc.2, errors.iter().map(|err| err.span.unwrap().start).collect::<Vec<_>>(), "{}", tc.0);
+    }
+
+    // A lexer which does not recover ends the parse when synchronizing runs into
+    // its error, keeping the statements before it.
+    let lexer = Lexer::new("let x = 1; fn f() { x; } let a = ; @ c;".to_string());
+    let (arena, errors) = tokens_parser::TokensParser::new(lexer.tokens()).parse_recovering();
+
+    assert_eq!(2, arena.statements().len());
+    assert!(matches!(arena.statement(arena.statements()[1]), Some(StmtKind::Function { .. })));
+    assert_eq!(vec![33, 35], errors.iter().map(|err| err.span.unwrap().start).collect::<Vec<_>>());
+    assert!(matches!(errors[1].kind, error::ErrorKind::Lexer(_)));
+
+    // Running out of tokens after a lexer error reads as the end of the input.
+    let lexer = Lexer::new("let a = 1; let b = @; c;".to_string());
+    let (arena, errors) = tokens_parser::TokensParser::new(lexer.tokens()).parse_recovering();
+
+    assert_eq!(2, arena.statements().len());
+    assert!(matches!(arena.statement(arena.statements()[1]), Some(StmtKind::Error)));
+    assert_eq!(1, errors.len());
+
+    let lexer = Lexer::new("let a = @; const b = 1; b = 2;".to_string());
+    let (_, errors) = Parser::new(lexer).parse_recovering();
+
+    assert!(matches!(errors[0].kind, error::ErrorKind::Lexer(_)));
+    assert!(matches!(errors[1].kind, error::ErrorKind::ExpectExpression(_)));
+    assert!(matches!(errors[2].kind, error::ErrorKind::ConstAssignment(_)));
+}
+
+#[test]
+fn test_parse_const() {
+    let test_cases = vec![
+        ("const a = 1;", "(const a = 1)\n"),
+        ("const f = fn (x) { x }", "(const f = (fn (x) (block)\nx\n(end block)))\n"),
+        ("const a = 1; { let a = 2; a = 3; }", "(const a = 1)\n(block)\n(let a = 2)\n(= a 3)\n(end block)\n"),
+        ("const a = 1; fn f(a) { a += 1; }", "(const a = 1)\n(fn f (a) (block)\n(+= a 1)\n(end block))\n"),
+        ("const a = 1; for (let a in r) { a = 2; }", "(const a = 1)\n(for a in r)\n(block)\n(= a 2)\n(end block)\n(endfor)\n"),
+        ("const o = f(); o.x = 1", "(const o = (call f))\n(= (. o x) 1)\n"),
+        ("a = 1; const a = 2;", "(= a 1)\n(const a = 2)\n"),
+        ("fn f(a) { a = 2; } const a = 1;", "(fn f (a) (block)\n(= a 2)\n(end block))\n(const a = 1)\n"),
+        ("fn f() { let a = 2; a = 3; } const a = 1;", "(fn f () (block)\n(let a = 2)\n(= a 3)\n(end block))\n(const a = 1)\n"),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, parse_to_string(tc.0), "{}", tc.0);
+    }
+
+    for (input, position) in [("const a;", 6), ("const a", 6), ("{\n    const abc }", 12)] {
+        let lexer = Lexer::new(input.to_string());
+        let err = Parser::new(lexer).parse().unwrap_err();
+
+        assert!(matches!(err.kind, error::ErrorKind::ConstWithoutInitializer(_)), "{input}: {err}");
+        assert_eq!(Some(position), err.span.map(|s| s.start), "{input}");
+    }
+
+    let mut assignments: Vec<String> = EBNF_ASSIGNMENT_OPS.iter().map(|op| format!("const a = 1; a {op} 2")).collect();
+    assignments.extend([
+        "const a = 1; { a = 2; }",
+        "const a = 1; fn f() { a = 2; }",
+        "const a = 1; let f = fn () { a += 1; };",
+        "const a = 1; { let a = 2; } a = 3",
+        "const a = 1; while (true) a <<= 1;",
+        "const a = 1; class C { m() { a = 2; } }",
+        "const a = 1; f(b = a = 2)",
+        "fn g() { a = 2; } const a = 1; g(); a",
+        "let f = () => { a += 1; }; const a = 1;",
+        "{ fn g() { a = 2; } } const a = 1;",
+        "class C { m() { a = 2; } } const a = 1;",
+        "class C { v = a = 2; } const a = 1;",
+    ].map(String::from));
+
+    for input in assignments {
+        let lexer = Lexer::new(input.clone());
+        let err = Parser::new(lexer).parse().unwrap_err();
+
+        assert!(matches!(&err.kind, error::ErrorKind::ConstAssignment(name) if name == "a"), "{input}: {err}");
+    }
+}
+
+#[derive(Default)]
+struct PostfixSpans {
+    offsets: Vec<usize>,
+}
+
+impl PostfixSpans {
+    fn push(&mut self, expression: &dyn Expression) {
+        self.offsets.push(expression.token().unwrap().span.unwrap().start);
+    }
+}
+
+impl ExpressionVisitor for PostfixSpans {
+    fn visit_identifier(&mut self, _identifier: &Identifier) {}
+    fn visit_literal(&mut self, _literal: &Literal) {}
+    fn visit_binary(&mut self, _binary: &Binary) {}
+    fn visit_unary(&mut self, _unary: &Unary) {}
+    fn visit_if(&mut self, _if_expr: &If) {}
+    fn visit_function_literal(&mut self, _function: &FunctionLiteral) {}
+    fn visit_range(&mut self, _range: &Range) {}
+    fn visit_fstring(&mut self, _fstring: &FString) {}
+
+    fn visit_call(&mut self, call: &Call) {
+        self.push(call.callee.as_ref());
+        call.callee.accept(self);
+
+        for argument in &call.arguments {
+            self.push(argument.as_ref());
+        }
+    }
+
+    fn visit_member(&mut self, member: &Member) {
+        self.push(member.object.as_ref());
+        member.object.accept(self);
+        self.push(member.property.as_ref());
+    }
+
+    fn visit_index(&mut self, index: &Index) {
+        self.push(index.object.as_ref());
+        index.object.accept(self);
+        self.push(index.index.as_ref());
+    }
+}
+
+impl StatementVisitor for PostfixSpans {
+    fn visit_ast(&mut self, ast: &Ast) {
+        for statement in ast.statements() {
+            statement.accept(self);
+        }
+    }
+
+    fn visit_let(&mut self, _let_statement: &Let) {}
+    fn visit_const(&mut self, _const_statement: &Const) {}
+    fn visit_return(&mut self, _return_statement: &Return) {}
+    fn visit_block(&mut self, _block: &Block) {}
+    fn visit_function(&mut self, _function: &Function) {}
+    fn visit_while(&mut self, _while_statement: &While) {}
+    fn visit_for(&mut self, _for_statement: &For) {}
+    fn visit_for_in(&mut self, _for_in: &ForIn) {}
+    fn visit_break(&mut self, _break_statement: &Break) {}
+    fn visit_continue(&mut self, _continue_statement: &Continue) {}
+    fn visit_class(&mut self, _class: &Class) {}
+    fn visit_error_statement(&mut self, _error_statement: &ErrorStatement) {}
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.push(expr.expression.as_ref());
+        expr.expression.accept(self);
+    }
+}
+
+#[test]
+fn test_node_spans() {
+    // Every expression covers its whole input.
+    let expressions = [
+        "a + b * c",
+        "(a + b) * c",
+        "-(a)",
+        "f(a, b).x[i](y)",
+        "(f)(x).y",
+        "a = b += 1",
+        "0..=10",
+        "if (a) { b } else if (c) { d }",
+        "fn (x) { x }",
+        "(x, y) => x + y",
+        "f\"a${b}c\"",
+    ];
+
+    for input in expressions {
+        let expression = Parser::parse_expression(input).unwrap();
+
+        assert_eq!(Some(Span::new(0, input.len())), expression.span(), "{input}");
+    }
+
+    let statements = [
+        "let a = 1;",
+        "const a = 1",
+        "return a + 1;",
+        "{ a; { b } }",
+        "fn f(a) { return a; }",
+        "while (a) { break; }",
+        "for (let i = 0; i < 3; i += 1) a;",
+        "for (let x in xs) {}",
+        "class A extends B { x = 1; m() {} }",
+        "a.b(c);",
+    ];
+
+    for input in statements {
+        let statement = Parser::parse_statement(input).unwrap();
+
+        assert_eq!(Some(Span::new(0, input.len())), statement.span(), "{input}");
+    }
+
+    // Parentheses around a whole expression are not part of its node.
+    assert_eq!(Some(Span::new(1, 5)), Parser::parse_expression("(a + b)").unwrap().span());
+}
+
+#[test]
+fn test_span_helpers() {
+    let span = Span::new(4, 3);
+
+    assert_eq!(7, span.end());
+    assert_eq!(Span::new(2, 5), span.merge(Span::new(2, 1)));
+    assert_eq!(Span::new(4, 8), span.merge(Span::new(10, 2)));
+    assert_eq!(Span::new(4, 3), span.merge(Span::new(5, 1)));
+    assert!(!span.contains(3));
+    assert!(span.contains(4));
+    assert!(span.contains(6));
+    assert!(!span.contains(7));
+    assert!(!Span::new(4, 0).contains(4));
+}
+
+#[test]
+fn test_postfix_spans() {
+    let test_cases = vec![
+        // Each suffix node's token is its opening token.
+        ("f(a, b).x[i](y)", vec![12, 9, 7, 1, 0, 2, 5, 8, 10, 13]),
+        ("a.b.c", vec![3, 1, 0, 2, 4]),
+        ("m[0][1]", vec![4, 1, 0, 2, 5]),
+    ];
+
+    for tc in test_cases {
+        let lexer = Lexer::new(tc.0.to_string());
+        let ast = Parser::new(lexer).parse().unwrap();
+        let mut spans = PostfixSpans::default();
+        spans.visit_ast(&ast);
+
+        assert_eq!(tc.1, spans.offsets, "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_parse_arena() {
+    let input = "let a = 1 + 2; fn f(x) { return x; } a = f(a);";
+    let parser = Parser::new(Lexer::new(input.to_string()));
+    let arena = parser.parse_arena().unwrap();
+
+    let [first, second, third] = arena.statements() else {
+        panic!("expected 3 statements, got {:?}", arena.statements());
+    };
+
+    let Some(&StmtKind::Let { identifier, expression: Some(expression) }) = arena.statement(*first) else {
+        panic!("{:?}", arena.statement(*first));
+    };
+    assert!(matches!(arena.expression(identifier), Some(ExprKind::Identifier(name)) if name == "a"));
+    assert!(matches!(arena.expression(expression), Some(ExprKind::Binary { op: BinaryOp::Plus, .. })));
+    assert_eq!(Some(Span::new(8, 5)), arena.span(expression));
+
+    // Tokens are read back from the source.
+    let token = arena.token(expression).unwrap();
+    assert_eq!((TokenType::IntNumber, "1", Some(Span::new(8, 1))), (token.token_type, &*token.lexeme, token.span));
+    assert!(matches!(token.lexeme, Cow::Borrowed(_)));
+
+    assert!(matches!(arena.statement(*second), Some(StmtKind::Function { parameters, .. }) if parameters.len() == 1));
+    assert!(matches!(arena.statement(*third), Some(StmtKind::Expr(_))));
+
+    // Statements are not expressions and ids start at 1.
+    assert!(arena.expression(*first).is_none());
+    assert!(arena.get(NodeId(0)).is_none());
+    assert!(arena.get(NodeId(arena.len() as u32 + 1)).is_none());
+
+    let identifiers = arena.nodes().iter()
+        .filter(|node| matches!(node.kind, NodeKind::Expression(ExprKind::Identifier(_))))
+        .count();
+    assert_eq!(7, identifiers);
+
+    // Lowering keeps the ids, spans and tokens.
+    let ast = parser.parse().unwrap();
+    for (&id, statement) in arena.statements().iter().zip(ast.statements()) {
+        assert_eq!(Some(id), statement.id());
+        assert_eq!(arena.span(id), statement.span());
+        assert_eq!(arena.token(id).map(Token::into_owned).as_ref(), statement.token());
+    }
+
+    assert_eq!(TestPrinter.visit_ast(&ast), TestPrinter.visit_ast(&arena.into_ast()));
+
+    let parser = Parser::new(Lexer::new("let a = ; let b = 2;".to_string()));
+    let (arena, errors) = parser.parse_arena_recovering();
+
+    assert_eq!(1, errors.len());
+    assert!(matches!(arena.statement(arena.statements()[0]), Some(StmtKind::Error)));
+    assert!(matches!(arena.statement(arena.statements()[1]), Some(StmtKind::Let { .. })));
+}
+
+#[test]
+fn test_error_display() {
+    let test_cases = vec![
+        (
+            "let a = 1;\nlet b = ;",
+            "Parse error at 2:9: expect expression, got SEMICOLON(;)",
+            "Parse error at 19..20: expect expression, got SEMICOLON(;)",
+        ),
+        (
+            "let a = 1;\r\n\nlet b = 2 /* c",
+            "Parse error at 3:11: Lexer error at 3:11: unterminated block comment",
+            "Parse error at 23..25: Lexer error at 23..25: unterminated block comment",
+        ),
+        (
+            "let a = 1;\nlet b = @;",
+            "Parse error at 2:9: Lexer error at 2:9: unexpected char '@'",
+            "Parse error at 19..20: Lexer error at 19..20: unexpected char '@'",
+        ),
+    ];
+
+    for tc in test_cases {
+        let err = Parser::new(Lexer::new(tc.0.to_string())).parse().unwrap_err();
+        let source_map = SourceMap::new(tc.0);
 
-        assert_eq!(tc.1, test_printer.buffer);
+        assert_eq!(tc.1, err.display_with(&source_map).to_string(), "{}", tc.0);
+        assert_eq!(tc.2, err.to_string(), "{}", tc.0);
     }
 }
@@ -1,86 +1,275 @@
 use std::mem;
 
 use crate::{
-    ast, lexer::Tokens, span::*, token::*
+    ast::{self, ExprKind, NodeId, StmtKind}, lexer::{Error as LexerError, Lookahead, Tokens}, span::*, token::*
 };
 
 use super::error::*;
 
 type TT = TokenType;
-type BoxStatement = Box<dyn ast::Statement>;
-type BoxExpression = Box<dyn ast::Expression>;
 
-const EXPRESSION_START_TTS: [TT; 11] = [
-    TT::IntNumber, TT::String, TT::True, TT::False, TT::FloatNumber, TT::Identifier, TT::Lparen, TT::Minus, TT::Not,
-    TT::BitNot, TT::If,
+const EXPRESSION_START_TTS: [TT; 18] = [
+    TT::IntNumber, TT::String, TT::FStringStart, TT::True, TT::False, TT::FloatNumber, TT::Null, TT::Identifier, TT::Constructor, TT::Extends,
+    TT::Get, TT::Set, TT::Lparen, TT::Minus, TT::Not, TT::BitNot, TT::If, TT::Fn,
 ];
 
 const TERMINAL_TTS: [TT; 1] = [TT::Semicolon];
-const ASSIGN_OP_TTS: [TT; 11] = [
-    TT::Assign, TT::AssignBitAnd, TT::AssignBitOr, TT::AssignBitXor, TT::AssignDiv, TT::AssignMinus, TT::AssignMod,
-    TT::AssignMult, TT::AssignPlus, TT::AssignShiftLeft, TT::AssignShiftRight
-];
+
+// Keywords which only mean something inside a class declaration are valid identifiers everywhere else.
+const IDENTIFIER_TTS: [TT; 5] = [TT::Identifier, TT::Constructor, TT::Extends, TT::Get, TT::Set];
+
+const PROPERTY_NAME_TTS: [TT; 6] = [TT::Identifier, TT::Constructor, TT::Extends, TT::Get, TT::Set, TT::Static];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Associativity {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OperatorKind {
+    Binary(ast::BinaryOp),
+    Range { inclusive: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BinaryOperator {
+    token_type: TT,
+    kind: OperatorKind,
+    precedence: u8,
+    associativity: Associativity,
+}
+
+impl BinaryOperator {
+    const fn new(token_type: TT, op: ast::BinaryOp, precedence: u8, associativity: Associativity) -> Self {
+        Self { token_type, kind: OperatorKind::Binary(op), precedence, associativity }
+    }
+
+    const fn range(token_type: TT, inclusive: bool, precedence: u8) -> Self {
+        Self { token_type, kind: OperatorKind::Range { inclusive }, precedence, associativity: Associativity::Left }
+    }
+}
+
+// Binary operators from the lowest to the highest precedence, one level per `ebnf` production.
+const BINARY_OPERATORS: [BinaryOperator; 33] = {
+    use ast::BinaryOp as Op;
+    use Associativity::*;
+
+    [
+        BinaryOperator::new(TT::Assign, Op::Assign, 1, Right),
+        BinaryOperator::new(TT::AssignPlus, Op::AssignPlus, 1, Right),
+        BinaryOperator::new(TT::AssignMinus, Op::AssignMinus, 1, Right),
+        BinaryOperator::new(TT::AssignMult, Op::AssignMult, 1, Right),
+        BinaryOperator::new(TT::AssignDiv, Op::AssignDiv, 1, Right),
+        BinaryOperator::new(TT::AssignMod, Op::AssignMod, 1, Right),
+        BinaryOperator::new(TT::AssignBitAnd, Op::AssignBitAnd, 1, Right),
+        BinaryOperator::new(TT::AssignBitOr, Op::AssignBitOr, 1, Right),
+        BinaryOperator::new(TT::AssignBitXor, Op::AssignBitXor, 1, Right),
+        BinaryOperator::new(TT::AssignShiftLeft, Op::AssignShiftLeft, 1, Right),
+        BinaryOperator::new(TT::AssignShiftRight, Op::AssignShiftRight, 1, Right),
+        BinaryOperator::new(TT::AssignUnsignedShiftRight, Op::AssignUnsignedShiftRight, 1, Right),
+        BinaryOperator::range(TT::Range, false, 2),
+        BinaryOperator::range(TT::RangeInclusive, true, 2),
+        BinaryOperator::new(TT::Or, Op::Or, 3, Left),
+        BinaryOperator::new(TT::And, Op::And, 4, Left),
+        BinaryOperator::new(TT::Gt, Op::Gt, 5, Left),
+        BinaryOperator::new(TT::Gte, Op::Gte, 5, Left),
+        BinaryOperator::new(TT::Lt, Op::Lt, 5, Left),
+        BinaryOperator::new(TT::Lte, Op::Lte, 5, Left),
+        BinaryOperator::new(TT::Eq, Op::Eq, 6, Left),
+        BinaryOperator::new(TT::Neq, Op::Neq, 6, Left),
+        BinaryOperator::new(TT::BitOr, Op::BitOr, 7, Left),
+        BinaryOperator::new(TT::BitXor, Op::BitXor, 8, Left),
+        BinaryOperator::new(TT::BitAnd, Op::BitAnd, 9, Left),
+        BinaryOperator::new(TT::ShiftLeft, Op::ShiftLeft, 10, Left),
+        BinaryOperator::new(TT::ShiftRight, Op::ShiftRight, 10, Left),
+        BinaryOperator::new(TT::UnsignedShiftRight, Op::UnsignedShiftRight, 10, Left),
+        BinaryOperator::new(TT::Plus, Op::Plus, 11, Left),
+        BinaryOperator::new(TT::Minus, Op::Minus, 11, Left),
+        BinaryOperator::new(TT::Mult, Op::Mult, 12, Left),
+        BinaryOperator::new(TT::Div, Op::Div, 12, Left),
+        BinaryOperator::new(TT::Mod, Op::Mod, 12, Left),
+    ]
+};
+
+// Tokens the grammar cares about, leaving out comments and the `Error` tokens
+// a recovering lexer has already reported.
+#[derive(Debug)]
+struct SignificantTokens<'a>(Tokens<'a>);
+
+impl<'a> Iterator for SignificantTokens<'a> {
+    type Item = Result<Token<'a>, LexerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find(is_significant)
+    }
+}
 
 #[derive(Debug)]
 pub(super) struct TokensParser<'a> {
-    tokens: Tokens<'a>,
-    current_token: Token,
-    peek_token: Token,
-    node_id_gen: ast::NodeIdGen,
+    tokens: Lookahead<SignificantTokens<'a>>,
+    current_token: Token<'a>,
+    arena: ast::Arena<'a>,
+    loop_depth: usize,
+    // Span of the last token consumed, where the node being finished ends.
+    previous_span: Option<Span>,
+    // Errors the parser recovered from, `None` unless it was asked to recover.
+    errors: Option<Vec<Error>>,
 }
 
 impl<'a> TokensParser<'a> {
     pub(super) fn new(tokens: Tokens<'a>) -> Self {
         Self {
-            tokens,
+            arena: ast::Arena::new(tokens.source()),
+            tokens: Lookahead::new(SignificantTokens(tokens)),
             current_token: Default::default(),
-            peek_token: Default::default(),
-            node_id_gen: Default::default(),
+            loop_depth: 0,
+            previous_span: None,
+            errors: None,
         }
     }
 }
 
-impl TokensParser<'_> {
-    pub(super) fn parse(mut self) -> Result<ast::Ast, Error> {
+impl<'a> TokensParser<'a> {
+    pub(super) fn parse(mut self) -> Result<ast::Arena<'a>, Error> {
         self.advance()?;
+        let statements = self.parse_program()?;
+        self.arena.set_statements(statements);
+
+        Ok(self.arena)
+    }
+
+    pub(super) fn parse_single_expression(self) -> Result<(ast::Arena<'a>, NodeId), Error> {
+        self.parse_all(Self::parse_expression)
+    }
+
+    pub(super) fn parse_single_statement(self) -> Result<(ast::Arena<'a>, NodeId), Error> {
+        self.parse_all(Self::parse_statement)
+    }
+
+    pub(super) fn parse_single_block(self) -> Result<(ast::Arena<'a>, NodeId), Error> {
+        self.parse_all(Self::parse_block)
+    }
+
+    // Runs `parse` on the input, which it has to consume entirely.
+    fn parse_all(mut self, parse: impl FnOnce(&mut Self) -> Result<NodeId, Error>) -> Result<(ast::Arena<'a>, NodeId), Error> {
         self.advance()?;
-        self.parse_program()
+        let id = parse(&mut self)?;
+        self.expect_advance(&[TT::Eof])?;
+
+        Ok((self.arena, id))
     }
 
-    fn parse_program(&mut self) -> Result<ast::Ast, Error> {
+    /// Parses the whole input even if it has errors, replacing every statement
+    /// that fails to parse with an `ErrorStatement`. Lexer errors come along
+    /// when `tokens` is a recovering lexer.
+    pub(super) fn parse_recovering(mut self) -> (ast::Arena<'a>, Vec<Error>) {
+        self.errors = Some(vec![]);
+
+        // Statements parsed before an error the parser could not get past are kept.
         let mut statements = vec![];
+        if let Err(err) = self.advance().and_then(|_| self.parse_statements(&mut statements)) {
+            self.recover(err);
+        }
+        self.arena.set_statements(statements);
 
-        loop {
-            if self.current_token_type() == TokenType::Eof {
-                break;
-            }
+        let mut errors = self.errors.take().unwrap_or_default();
+        errors.extend(self.tokens.inner().0.diagnostics().iter().cloned().map(Error::from));
+        errors.sort_by_key(|err| err.span.map(|span| span.start));
 
+        (self.arena, errors)
+    }
+
+    fn parse_program(&mut self) -> Result<Vec<NodeId>, Error> {
+        let mut statements = vec![];
+        self.parse_statements(&mut statements)?;
+
+        Ok(statements)
+    }
+
+    fn parse_statements(&mut self, statements: &mut Vec<NodeId>) -> Result<(), Error> {
+        while self.current_token_type() != TokenType::Eof {
             statements.push(
-                self.parse_statement()?.into()
+                self.parse_statement_or_recover()?
             );
         }
 
-        Ok(
-            ast::Ast::new(statements)
-        )
+        Ok(())
+    }
+
+    fn parse_statement_or_recover(&mut self) -> Result<NodeId, Error> {
+        let token = self.current_token.clone();
+
+        match self.parse_statement() {
+            Err(err) if self.errors.is_some() => {
+                self.recover(err);
+
+                // Always make progress, the statement may have failed on its first token.
+                if self.current_token.span == token.span && self.current_token_type() != TT::Eof {
+                    self.advance()?;
+                }
+
+                self.synchronize()?;
+
+                Ok(self.make_statement_node(StmtKind::Error, Some(token)))
+            }
+            result => result,
+        }
+    }
+
+    fn recover(&mut self, err: Error) {
+        if let Some(errors) = &mut self.errors {
+            errors.push(err);
+        }
+    }
+
+    // Skips tokens up to the next statement boundary: past a `;`, or up to a `}`
+    // or a keyword starting a statement. Braced groups are skipped as a whole.
+    fn synchronize(&mut self) -> Result<(), Error> {
+        let mut depth = 0usize;
+
+        loop {
+            match self.current_token_type() {
+                TT::Eof => return Ok(()),
+                TT::Lbrace => depth += 1,
+                TT::Rbrace if depth == 0 => return Ok(()),
+                TT::Rbrace => depth -= 1,
+                TT::Semicolon if depth == 0 => {
+                    self.advance()?;
+                    return Ok(());
+                }
+                TT::Let | TT::Const | TT::Fn | TT::Class | TT::Return | TT::While | TT::For
+                | TT::Break | TT::Continue if depth == 0 => return Ok(()),
+                _ => {}
+            }
+
+            self.advance()?;
+        }
     }
 
-    fn parse_statement(&mut self) -> Result<BoxStatement, Error> {
+    fn parse_statement(&mut self) -> Result<NodeId, Error> {
         match self.current_token_type() {
             TT::Let => self.parse_let_statement(),
+            TT::Const => self.parse_const_statement(),
             TT::Return => self.parse_retrun_statement(),
             TT::Lbrace => self.parse_block(),
+            TT::Fn if IDENTIFIER_TTS.contains(&self.peek_token_type(0)) => self.parse_function_statement(),
+            TT::Class => self.parse_class_statement(),
+            TT::If => self.parse_if_statement(),
+            TT::While => self.parse_while_statement(),
+            TT::For => self.parse_for_statement(),
+            TT::Break | TT::Continue => self.parse_loop_control_statement(),
             _ => {
                 if self.current_token_type_is(&EXPRESSION_START_TTS) {
                     self.parse_expression_statement()
                 } else {
-                    let mut expected = vec![TT::Let, TT::Return];
+                    let mut expected = vec![TT::Let, TT::Const, TT::Return, TT::Class, TT::While, TT::For, TT::Break, TT::Continue];
                     expected.extend(EXPRESSION_START_TTS);
 
                     Err(Error::new(
                         ErrorKind::ExpectStatement(
                             UnexpectedTokenError {
-                                token: self.current_token.clone(),
+                                token: Box::new(self.current_token.clone().into_owned()),
                                 expected
                             }
                         ),
@@ -91,10 +280,14 @@ impl TokensParser<'_> {
         }
     }
 
-    fn parse_let_statement(&mut self) -> Result<BoxStatement, Error> {
+    fn parse_let_statement(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::Let])?;
         let identifier = self.parse_idetifier()?;
-        
+
+        self.parse_let_statement_rest(token, identifier)
+    }
+
+    fn parse_let_statement_rest(&mut self, token: Token<'a>, identifier: NodeId) -> Result<NodeId, Error> {
         let expression = if self.current_token_type_is(&[TokenType::Assign]) {
             self.advance()?;
             Some(self.parse_expression()?)
@@ -104,12 +297,36 @@ impl TokensParser<'_> {
 
         self.parse_terminal()?;
 
-        let statement = ast::Let::new(identifier, expression);
+        let statement = StmtKind::Let { identifier, expression };
 
         Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_retrun_statement(&mut self) -> Result<BoxStatement, Error> {
+    fn parse_const_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Const])?;
+        let identifier = self.parse_idetifier()?;
+
+        if self.advance_if(&[TT::Assign])?.is_none() {
+            let name = match self.arena.expression(identifier) {
+                Some(ExprKind::Identifier(name)) => name.clone(),
+                _ => String::new(),
+            };
+
+            return Err(Error::new(
+                ErrorKind::ConstWithoutInitializer(name),
+                self.arena.span(identifier)
+            ));
+        }
+
+        let expression = self.parse_expression()?;
+        self.parse_terminal()?;
+
+        let statement = StmtKind::Const { identifier, expression };
+
+        Ok(self.make_statement_node(statement, Some(token)))
+    }
+
+    fn parse_retrun_statement(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::Return])?;
         let expression = if self.current_token_type_is(&EXPRESSION_START_TTS) {
             Some(self.parse_expression()?)
@@ -119,227 +336,300 @@ impl TokensParser<'_> {
 
         self.parse_terminal()?;
 
-        let statement = ast::Return::new(expression);
+        let statement = StmtKind::Return(expression);
 
         Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_block(&mut self) -> Result<BoxStatement, Error> {
+    fn parse_block(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::Lbrace])?;
         let mut statements = vec![];
 
-        while !self.current_token_type_is(&[TT::Rbrace]) {
-            statements.push(self.parse_statement()?);
+        while !self.current_token_type_is(&[TT::Rbrace, TT::Eof]) {
+            statements.push(self.parse_statement_or_recover()?);
         }
 
-        self.advance()?;
+        self.expect_advance(&[TT::Rbrace])?;
 
-        let statement = ast::Block::new(statements);
+        let statement = StmtKind::Block(statements);
         Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_expression_statement(&mut self) -> Result<BoxStatement, Error> {
-        let token = self.current_token.clone();
-        let expression = self.parse_expression()?;
-        self.parse_terminal()?;
-        let statement = ast::Expr::new(expression);
+    fn parse_function_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Fn])?;
+        let name = self.parse_idetifier()?;
+        let parameters = self.parse_parameter_list()?;
+        let body = self.parse_function_body()?;
 
+        let statement = StmtKind::Function { name, parameters, body };
         Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_expression(&mut self) -> Result<BoxExpression, Error> {
-        match self.current_token_type() {
-            TT::If => self.parse_if(),
-            _ => self.parse_assigment()
-        }
-    }
+    fn parse_function_body(&mut self) -> Result<NodeId, Error> {
+        // `break` and `continue` can not jump out of a function.
+        let loop_depth = mem::take(&mut self.loop_depth);
+        let result = self.parse_block();
+        self.loop_depth = loop_depth;
 
-    fn parse_if(&mut self) -> Result<BoxExpression, Error> {
-        let token = self.expect_advance(&[TT::If])?;
-        self.expect_advance(&[TT::Lparen])?;
-        let condition = self.parse_expression()?;
-        self.expect_advance(&[TT::Rparen])?;
-        let consequence = self.parse_statement()?;
+        result
+    }
 
-        let alternative = if self.current_token_type_is(&[TT::Else]) {
-            self.advance()?;
-            Some(self.parse_statement()?)
+    fn parse_class_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Class])?;
+        let name = self.parse_idetifier()?;
+        let superclass = if self.advance_if(&[TT::Extends])?.is_some() {
+            Some(self.parse_idetifier()?)
         } else {
             None
         };
+        self.expect_advance(&[TT::Lbrace])?;
 
-        let expression = ast::If::new(condition, consequence, alternative);
-        Ok(self.make_expression_node(expression, Some(token)))
+        let mut members = vec![];
+        while !self.current_token_type_is(&[TT::Rbrace]) {
+            members.push(self.parse_class_member()?);
+        }
+
+        self.advance()?;
+
+        let statement = StmtKind::Class { name, superclass, members };
+        Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_assigment(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_logic_and()?;
-
-        if self.current_token_type_is(&ASSIGN_OP_TTS) {
-            let token = self.advance()?;
-            let right = self.parse_logic_or()?;
-            let op = match token.token_type {
-                TT::Assign => ast::BinaryOp::Assign,
-                TT::AssignBitAnd => ast::BinaryOp::AssignBitAnd,
-                TT::AssignBitOr => ast::BinaryOp::AssignBitOr,
-                TT::AssignBitXor => ast::BinaryOp::AssignBitXor,
-                TT::AssignDiv => ast::BinaryOp::AssignDiv,
-                TT::AssignMinus => ast::BinaryOp::AssignMinus,
-                TT::AssignMod => ast::BinaryOp::AssignMod,
-                TT::AssignMult => ast::BinaryOp::AssignMult,
-                TT::AssignPlus => ast::BinaryOp::AssignPlus,
-                TT::AssignShiftLeft => ast::BinaryOp::AssignShiftLeft,
-                TT::AssignShiftRight => ast::BinaryOp::AssignShiftRight,
-                _ => unreachable!(),
+    fn parse_class_member(&mut self) -> Result<ast::arena::ClassMember, Error> {
+        // `static`, `get`, `set` and `constructor` are modifiers only when followed by
+        // what they modify, otherwise they are the name of the member itself.
+        let is_static = self.current_token_type_is(&[TT::Static])
+            && PROPERTY_NAME_TTS.contains(&self.peek_token_type(0));
+        if is_static {
+            self.advance()?;
+        }
+
+        let kind = match self.current_token_type() {
+            TT::Constructor if !is_static && self.peek_token_type(0) == TT::Lparen => ast::MethodKind::Constructor,
+            TT::Get if PROPERTY_NAME_TTS.contains(&self.peek_token_type(0)) => {
+                self.advance()?;
+                ast::MethodKind::Getter
+            },
+            TT::Set if PROPERTY_NAME_TTS.contains(&self.peek_token_type(0)) => {
+                self.advance()?;
+                ast::MethodKind::Setter
+            },
+            _ => ast::MethodKind::Method,
+        };
+        let name = self.parse_property_name()?;
+
+        if kind == ast::MethodKind::Method && !is_static && !self.current_token_type_is(&[TT::Lparen]) {
+            let value = if self.advance_if(&[TT::Assign])?.is_some() {
+                Some(self.parse_expression()?)
+            } else {
+                None
             };
+            self.parse_terminal()?;
 
-            result = self.make_binary_expression_node(result, op, right);
+            return Ok(ast::arena::ClassMember::Property(ast::arena::Property { name, value }));
         }
 
-        Ok(result)
+        let parameters = self.parse_parameter_list()?;
+        let body = self.parse_function_body()?;
+
+        Ok(ast::arena::ClassMember::Method(ast::arena::Method { name, kind, is_static, parameters, body }))
     }
 
-    fn parse_logic_or(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_logic_and()?;
+    fn parse_while_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::While])?;
+        self.expect_advance(&[TT::Lparen])?;
+        let condition = self.parse_expression()?;
+        self.expect_advance(&[TT::Rparen])?;
+        let body = self.parse_loop_body()?;
 
-        if self.current_token_type_is(&[TT::Or]) {
-            self.advance()?;
-            let right = self.parse_logic_or()?;
-            let op = ast::BinaryOp::Or;
+        let statement = StmtKind::While { condition, body };
+        Ok(self.make_statement_node(statement, Some(token)))
+    }
 
-            result = self.make_binary_expression_node(result, op, right);
-        }
+    fn parse_for_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::For])?;
+        self.expect_advance(&[TT::Lparen])?;
 
-        Ok(result)
-    }
+        let initializer = match self.current_token_type() {
+            TT::Let => {
+                let let_token = self.advance()?;
+                let identifier = self.parse_idetifier()?;
 
-    fn parse_logic_and(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_equality()?;
+                if self.current_token_type_is(&[TT::In]) {
+                    return self.parse_for_in_statement_rest(token, identifier);
+                }
 
-        if self.current_token_type_is(&[TT::And]) {
-            self.advance()?;
-            let right = self.parse_logic_and()?;
-            let op = ast::BinaryOp::And;
+                Some(self.parse_let_statement_rest(let_token, identifier)?)
+            }
+            TT::Semicolon => {
+                self.advance()?;
+                None
+            }
+            _ => Some(self.parse_expression_statement()?),
+        };
 
-            result = self.make_binary_expression_node(result, op, right);
-        }
+        let condition = if self.current_token_type_is(&[TT::Semicolon]) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
 
-        Ok(result)
-    }
+        self.expect_advance(&[TT::Semicolon])?;
 
-    fn parse_equality(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_bit_or()?;
+        let update = if self.current_token_type_is(&[TT::Rparen]) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
 
-        if self.current_token_type_is(&[TT::Eq, TT::Neq, TT::Gt, TT::Gte, TT::Lt, TT::Lte]) {
-            let token = self.advance()?;
-            let right = self.parse_equality()?;
-            let op = match token.token_type {
-                TT::Eq => ast::BinaryOp::Eq,
-                TT::Neq => ast::BinaryOp::Neq,
-                TT::Gt => ast::BinaryOp::Gt,
-                TT::Gte => ast::BinaryOp::Gte,
-                TT::Lt => ast::BinaryOp::Lt,
-                TT::Lte => ast::BinaryOp::Lte,
-                _ => unreachable!(),
-            };
+        self.expect_advance(&[TT::Rparen])?;
+        let body = self.parse_loop_body()?;
 
-            result = self.make_binary_expression_node(result, op, right);
-        }
+        let statement = StmtKind::For { initializer, condition, update, body };
+        Ok(self.make_statement_node(statement, Some(token)))
+    }
 
-        Ok(result)
+    fn parse_for_in_statement_rest(&mut self, token: Token<'a>, variable: NodeId) -> Result<NodeId, Error> {
+        self.expect_advance(&[TT::In])?;
+        let iterable = self.parse_expression()?;
+        self.expect_advance(&[TT::Rparen])?;
+        let body = self.parse_loop_body()?;
+
+        let statement = StmtKind::ForIn { variable, iterable, body };
+        Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_bit_or(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_bit_and()?;
+    fn parse_loop_body(&mut self) -> Result<NodeId, Error> {
+        self.loop_depth += 1;
+        let result = self.parse_statement();
+        self.loop_depth -= 1;
 
-        if self.current_token_type_is(&[TT::BitOr, TT::BitXor]) {
-            let token = self.advance()?;
-            let right = self.parse_bit_or()?;
-            let op = match token.token_type {
-                TT::BitOr => ast::BinaryOp::BitOr,
-                TT::BitXor => ast::BinaryOp::BitXor,
-                _ => unreachable!(),
-            };
+        result
+    }
 
-            result = self.make_binary_expression_node(result, op, right);
+    fn parse_loop_control_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Break, TT::Continue])?;
+
+        if self.loop_depth == 0 {
+            let span = token.span;
+            return Err(Error::new(ErrorKind::OutsideLoop(Box::new(token.into_owned())), span));
         }
 
-        Ok(result)
+        self.parse_terminal()?;
+
+        match token.token_type {
+            TT::Break => Ok(self.make_statement_node(StmtKind::Break, Some(token))),
+            TT::Continue => Ok(self.make_statement_node(StmtKind::Continue, Some(token))),
+            _ => unreachable!(),
+        }
     }
 
-    fn parse_bit_and(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_shift()?;
+    fn parse_parameter_list(&mut self) -> Result<Vec<NodeId>, Error> {
+        self.expect_advance(&[TT::Lparen])?;
+        let mut parameters = vec![];
 
-        if self.current_token_type_is(&[TT::BitAnd]) {
-            self.advance()?;
-            let right = self.parse_bit_and()?;
-            let op = ast::BinaryOp::BitAnd;
+        if !self.current_token_type_is(&[TT::Rparen]) {
+            loop {
+                parameters.push(self.parse_idetifier()?);
 
-            result = self.make_binary_expression_node(result, op, right);
+                if self.advance_if(&[TT::Comma])?.is_none() {
+                    break;
+                }
+            }
         }
 
-        Ok(result)
+        self.expect_advance(&[TT::Rparen])?;
+
+        Ok(parameters)
     }
 
-    fn parse_shift(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_term()?;
+    fn parse_if_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.current_token.clone();
+        let expression = self.parse_if()?;
+        self.advance_if(&[TT::Semicolon])?;
+        let statement = StmtKind::Expr(expression);
 
-        if self.current_token_type_is(&[TT::ShiftLeft, TT::ShiftRight]) {
-            let token = self.advance()?;
-            let right = self.parse_shift()?;
-            let op = match token.token_type {
-                TT::ShiftLeft => ast::BinaryOp::ShiftLeft,
-                TT::ShiftRight => ast::BinaryOp::ShiftRight,
-                _ => unreachable!()
-            };
+        Ok(self.make_statement_node(statement, Some(token)))
+    }
 
-            result = self.make_binary_expression_node(result, op, right);
-        }
+    fn parse_expression_statement(&mut self) -> Result<NodeId, Error> {
+        let token = self.current_token.clone();
+        let expression = self.parse_expression()?;
+        self.parse_terminal()?;
+        let statement = StmtKind::Expr(expression);
 
-        Ok(result)
+        Ok(self.make_statement_node(statement, Some(token)))
     }
 
-    fn parse_term(&mut self) -> Result<BoxExpression, Error> {
-        let mut result = self.parse_factor()?;
+    fn parse_expression(&mut self) -> Result<NodeId, Error> {
+        match self.current_token_type() {
+            TT::If => self.parse_if(),
+            _ => self.parse_binary(0)
+        }
+    }
 
-        if self.current_token_type_is(&[TT::Plus, TT::Minus]) {
-            let token = self.advance()?;
-            let right = self.parse_term()?;
-            let op = match token.token_type {
-                TT::Plus => ast::BinaryOp::Plus,
-                TT::Minus => ast::BinaryOp::Minus,
-                _ => unreachable!(),
-            };
+    fn parse_if(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::If])?;
+        self.expect_advance(&[TT::Lparen])?;
+        let condition = self.parse_expression()?;
+        self.expect_advance(&[TT::Rparen])?;
+        let consequence = self.parse_statement()?;
 
-            result = self.make_binary_expression_node(result, op, right);
-        }
+        let alternative = if self.current_token_type_is(&[TT::Else]) {
+            self.advance()?;
+            Some(self.parse_statement()?)
+        } else {
+            None
+        };
 
-        Ok(result)
+        let expression = ExprKind::If { condition, consequence, alternative };
+        Ok(self.make_expression_node(expression, Some(token)))
     }
 
-    fn parse_factor(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<NodeId, Error> {
+        // Taken before the operand, which may start with a `(` not part of its own span.
+        let start = self.current_span();
         let mut result = self.parse_unary()?;
 
-        if self.current_token_type_is(&[TT::Mult, TT::Div, TT::Mod]) {
-            let token = self.advance()?;
-            let right = self.parse_factor()?;
-            let op = match token.token_type {
-                TT::Mult => ast::BinaryOp::Mult,
-                TT::Div => ast::BinaryOp::Div,
-                TT::Mod => ast::BinaryOp::Mod,
-                _ => unreachable!(),
+        while let Some(operator) = binary_operator(self.current_token_type()) {
+            if operator.precedence < min_precedence {
+                break;
+            }
+
+            self.advance()?;
+
+            let next_min_precedence = match operator.associativity {
+                Associativity::Left => operator.precedence + 1,
+                Associativity::Right => operator.precedence,
             };
+            let right = self.parse_binary(next_min_precedence)?;
 
-            result = self.make_binary_expression_node(result, op, right);
+            result = match operator.kind {
+                OperatorKind::Binary(op) => self.make_binary_expression_node(result, op, right, start),
+                OperatorKind::Range { inclusive } => {
+                    let token = self.token_of(result);
+                    let expression = ExprKind::Range { start: result, end: right, inclusive };
+
+                    self.make_node(expression, token, start)
+                }
+            };
         }
 
         Ok(result)
     }
 
-    fn parse_unary(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_unary(&mut self) -> Result<NodeId, Error> {
         let unary_token = self.advance_if(&[TT::Minus, TT::Not, TT::BitNot])?;
 
         if let Some(unary_token) = unary_token {
+            if unary_token.token_type == TT::Minus && self.at_negative_int_only() {
+                let token = self.advance()?;
+                let value = decode_int(&token.lexeme, true).map_err(|kind| Error::new(kind, token.span))?;
+                let start = unary_token.span;
+
+                return Ok(self.make_node(ExprKind::Literal(ast::Literal::Int(value)), Some((&unary_token).into()), start));
+            }
+
             let expression = self.parse_unary()?;
             let op = match unary_token.token_type {
                 TT::Minus => ast::UnaryOp::Minus,
@@ -347,26 +637,92 @@ impl TokensParser<'_> {
                 TT::BitNot => ast::UnaryOp::BitNot,
                 _ => unreachable!(),
             };
-            let expression = ast::Unary{op, right: expression};
+            let expression = ExprKind::Unary { op, right: expression };
             Ok(self.make_expression_node(expression, Some(unary_token)))
         } else {
-            self.parse_primary()
+            self.parse_postfix()
         }
     }
 
-    fn parse_primary(&mut self) -> Result<BoxExpression, Error> {
+    // An int literal which only fits in an `i64` negated, like the magnitude of
+    // `i64::MIN`, and is not the start of a postfix expression. The minus before
+    // it is folded into the literal.
+    fn at_negative_int_only(&mut self) -> bool {
+        self.current_token_type() == TT::IntNumber
+            && !matches!(self.peek_token_type(0), TT::Lparen | TT::Dot | TT::Lbracket)
+            && matches!(decode_int(&self.current_token.lexeme, false), Err(ErrorKind::IntOverflow))
+    }
+
+    fn parse_postfix(&mut self) -> Result<NodeId, Error> {
+        let start = self.current_span();
+        let mut result = self.parse_primary()?;
+
+        loop {
+            result = match self.current_token_type() {
+                TT::Lparen => self.parse_call_suffix(result, start)?,
+                TT::Dot => self.parse_member_suffix(result, start)?,
+                TT::Lbracket => self.parse_index_suffix(result, start)?,
+                _ => break,
+            };
+        }
+
+        Ok(result)
+    }
+
+    fn parse_call_suffix(&mut self, callee: NodeId, start: Option<Span>) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Lparen])?;
+        let mut arguments = vec![];
+
+        if !self.current_token_type_is(&[TT::Rparen]) {
+            loop {
+                arguments.push(self.parse_expression()?);
+
+                if self.advance_if(&[TT::Comma])?.is_none() {
+                    break;
+                }
+            }
+        }
+
+        self.expect_advance(&[TT::Rparen])?;
+
+        let expression = ExprKind::Call { callee, arguments };
+        Ok(self.make_node(expression, Some((&token).into()), start))
+    }
+
+    fn parse_member_suffix(&mut self, object: NodeId, start: Option<Span>) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Dot])?;
+        let property = self.parse_property_name()?;
+
+        let expression = ExprKind::Member { object, property };
+        Ok(self.make_node(expression, Some((&token).into()), start))
+    }
+
+    fn parse_index_suffix(&mut self, object: NodeId, start: Option<Span>) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Lbracket])?;
+        let index = self.parse_expression()?;
+        self.expect_advance(&[TT::Rbracket])?;
+
+        let expression = ExprKind::Index { object, index };
+        Ok(self.make_node(expression, Some((&token).into()), start))
+    }
+
+    fn parse_primary(&mut self) -> Result<NodeId, Error> {
         match self.current_token_type() {
             TT::IntNumber => self.parse_int_literal(),
             TT::String => self.parse_string_literal(),
+            TT::FStringStart => self.parse_fstring(),
             TT::True | TT::False => self.parse_bool_literal(),
             TT::FloatNumber => self.parse_float_literal(),
-            TT::Identifier => self.parse_idetifier(),
+            TT::Null => self.parse_null_literal(),
+            TT::Identifier | TT::Constructor | TT::Extends | TT::Get | TT::Set => self.parse_idetifier(),
+            TT::Lparen if self.is_arrow_function() => self.parse_arrow_function(),
             TT::Lparen => self.parse_group(),
+            TT::Fn => self.parse_function_literal(),
             _ => {
                 Err(Error::new(
                     ErrorKind::ExpectExpression(
                         UnexpectedTokenError {
-                            token: self.current_token.clone(),
+                            token: Box::new(self.current_token.clone().into_owned()),
                             expected: EXPRESSION_START_TTS.to_vec()
                         }
                     ),
@@ -376,51 +732,147 @@ impl TokensParser<'_> {
         }
     }
 
-    fn parse_int_literal(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_int_literal(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::IntNumber])?;
-        let value: i64 = handle_result(token.lexeme.parse(), token.span)?;
-        let expression = ast::Literal::Int(value);
+        let value = decode_int(&token.lexeme, false).map_err(|kind| Error::new(kind, token.span))?;
+        let expression = ExprKind::Literal(ast::Literal::Int(value));
 
         Ok(self.make_expression_node(expression, Some(token)))
     }
 
-    fn parse_string_literal(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_string_literal(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::String])?;
         let value = &token.lexeme[1..token.lexeme.len() - 1];
         let value = handle_result(unescape_string(value), token.span)?;
-        let expression = ast::Literal::Str(value);
+        let expression = ExprKind::Literal(ast::Literal::Str(value));
+
+        Ok(self.make_expression_node(expression, Some(token)))
+    }
+
+    fn parse_fstring(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::FStringStart])?;
+        let mut parts = vec![];
+
+        loop {
+            match self.current_token_type() {
+                TT::FStringText => {
+                    let text = self.advance()?;
+                    let value = handle_result(unescape_string(&text.lexeme), text.span)?;
+                    parts.push(ast::arena::FStringPart::Text(value));
+                }
+                TT::InterpolationStart => {
+                    self.advance()?;
+                    parts.push(ast::arena::FStringPart::Expression(self.parse_expression()?));
+                    self.expect_advance(&[TT::InterpolationEnd])?;
+                }
+                _ => {
+                    self.expect_advance(&[TT::FStringText, TT::InterpolationStart, TT::FStringEnd])?;
+                    break;
+                }
+            }
+        }
 
+        let expression = ExprKind::FString(parts);
         Ok(self.make_expression_node(expression, Some(token)))
     }
 
-    fn parse_bool_literal(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_bool_literal(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::True, TT::False])?;
         let value = match token.token_type {
             TT::True => true,
             TT::False => false,
             _ => unreachable!()
         };
-        let expression = ast::Literal::Bool(value);
+        let expression = ExprKind::Literal(ast::Literal::Bool(value));
 
         Ok(self.make_expression_node(expression, Some(token)))
     }
 
-    fn parse_float_literal(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_float_literal(&mut self) -> Result<NodeId, Error> {
         let token = self.expect_advance(&[TT::FloatNumber])?;
         let value: f64 = handle_result(token.lexeme.parse(), token.span)?;
-        let expression = ast::Literal::Float(value);
+        let expression = ExprKind::Literal(ast::Literal::Float(value));
 
         Ok(self.make_expression_node(expression, Some(token)))
     }
 
-    fn parse_idetifier(&mut self) -> Result<BoxExpression, Error> {
-        let token = self.expect_advance(&[TT::Identifier])?;
-        let expression = ast::Identifier(token.lexeme.clone());
+    fn parse_null_literal(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Null])?;
+
+        Ok(self.make_expression_node(ExprKind::Literal(ast::Literal::Null), Some(token)))
+    }
+
+    fn parse_idetifier(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&IDENTIFIER_TTS)?;
+        let expression = ExprKind::Identifier(token.lexeme.to_string());
         
         Ok(self.make_expression_node(expression, Some(token)))
     }
 
-    fn parse_group(&mut self) -> Result<BoxExpression, Error> {
+    fn parse_property_name(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&PROPERTY_NAME_TTS)?;
+        let expression = ExprKind::Identifier(token.lexeme.to_string());
+
+        Ok(self.make_expression_node(expression, Some(token)))
+    }
+
+    fn parse_function_literal(&mut self) -> Result<NodeId, Error> {
+        let token = self.expect_advance(&[TT::Fn])?;
+        let parameters = self.parse_parameter_list()?;
+        let body = self.parse_function_body()?;
+
+        let expression = ExprKind::FunctionLiteral { parameters, body };
+        Ok(self.make_expression_node(expression, Some(token)))
+    }
+
+    // `(` must be followed by a parameter list, `)` and `=>`, which can be any
+    // number of tokens ahead.
+    fn is_arrow_function(&mut self) -> bool {
+        let mut n = 0;
+
+        if self.peek_token_type(n) != TT::Rparen {
+            loop {
+                if !IDENTIFIER_TTS.contains(&self.peek_token_type(n)) {
+                    return false;
+                }
+
+                n += 1;
+
+                match self.peek_token_type(n) {
+                    TT::Comma => n += 1,
+                    TT::Rparen => break,
+                    _ => return false,
+                }
+            }
+        }
+
+        self.peek_token_type(n + 1) == TT::Arrow
+    }
+
+    fn parse_arrow_function(&mut self) -> Result<NodeId, Error> {
+        let token = self.current_token.clone();
+        let parameters = self.parse_parameter_list()?;
+        self.expect_advance(&[TT::Arrow])?;
+
+        let body = if self.current_token_type_is(&[TT::Lbrace]) {
+            self.parse_function_body()?
+        } else {
+            // An expression body is returned as if it was `{ return <expression>; }`.
+            let start = self.current_span();
+            let loop_depth = mem::take(&mut self.loop_depth);
+            let expression = self.parse_expression();
+            self.loop_depth = loop_depth;
+
+            let expression = expression?;
+            let token = self.token_of(expression);
+            self.make_node(StmtKind::Return(Some(expression)), token, start)
+        };
+
+        let expression = ExprKind::FunctionLiteral { parameters, body };
+        Ok(self.make_expression_node(expression, Some(token)))
+    }
+
+    fn parse_group(&mut self) -> Result<NodeId, Error> {
         self.expect_advance(&[TT::Lparen])?;
         let result = self.parse_expression()?;
         self.expect_advance(&[TT::Rparen])?;
@@ -436,7 +888,7 @@ impl TokensParser<'_> {
                 return Err(Error::new(
                     ErrorKind::ExpectTerminal(
                         UnexpectedTokenError {
-                            token: self.current_token.clone(),
+                            token: Box::new(self.current_token.clone().into_owned()),
                             expected: TERMINAL_TTS.to_vec()
                         }
                     ),
@@ -448,13 +900,13 @@ impl TokensParser<'_> {
         Ok(())
     }
 
-    fn expect_advance(&mut self, token_types: &[TokenType]) -> Result<Token, Error> {
+    fn expect_advance(&mut self, token_types: &[TokenType]) -> Result<Token<'a>, Error> {
         if self.current_token_type_is(token_types) {
             Ok(self.advance()?)
         } else {
             Err(make_error(
                 UnexpectedTokenError {
-                    token: self.current_token.clone(),
+                    token: Box::new(self.current_token.clone().into_owned()),
                     expected: token_types.to_vec(),
                 },
                 self.current_span()
@@ -463,7 +915,7 @@ impl TokensParser<'_> {
     }
 
     fn current_token_type_is(&self, token_types: &[TokenType]) -> bool {
-        token_types.iter().any(|tt| *tt == self.current_token.token_type)
+        token_types.contains(&self.current_token.token_type)
     }
 
     #[inline]
@@ -476,44 +928,72 @@ impl TokensParser<'_> {
         self.current_token.span
     }
 
-    fn make_statement_node<T: ast::Statement + 'static>(&mut self, kind: T, token: Option<Token>) -> BoxStatement {
-        let id = self.node_id_gen.next_id();
-        ast::Node::new(id, kind, token).into()
+    fn make_statement_node(&mut self, kind: StmtKind, token: Option<Token<'_>>) -> NodeId {
+        let start = token.as_ref().and_then(|token| token.span);
+        self.make_node(kind, token.as_ref().map(Into::into), start)
     }
 
-    fn make_expression_node<T: ast::Expression + 'static>(&mut self, kind: T, token: Option<Token>) -> BoxExpression {
-        let id = self.node_id_gen.next_id();
-        ast::Node::new(id, kind, token).into()
+    fn make_expression_node(&mut self, kind: ExprKind, token: Option<Token<'_>>) -> NodeId {
+        let start = token.as_ref().and_then(|token| token.span);
+        self.make_node(kind, token.as_ref().map(Into::into), start)
     }
 
-    fn make_binary_expression_node(&mut self, left: BoxExpression, op: ast::BinaryOp, right: BoxExpression) -> BoxExpression {
-        let token = left.token().cloned();
-        let expression = ast::Binary{ left, op, right };
-        
-        self.make_expression_node(expression, token)
+    // The node spans from `start` to the last token consumed. `start` is not the
+    // token's own span for nodes beginning with an operand, like `a + b` or `f(x)`.
+    fn make_node(&mut self, kind: impl Into<ast::NodeKind>, token: Option<ast::TokenRef>, start: Option<Span>) -> NodeId {
+        let span = match (start, self.previous_span) {
+            (Some(start), Some(end)) => Some(start.merge(end)),
+            (start, _) => start,
+        };
+
+        self.arena.alloc(kind, token, span)
+    }
+
+    fn token_of(&self, id: NodeId) -> Option<ast::TokenRef> {
+        self.arena.get(id).and_then(|node| node.token)
+    }
+
+    fn make_binary_expression_node(&mut self, left: NodeId, op: ast::BinaryOp, right: NodeId, start: Option<Span>) -> NodeId {
+        let token = self.token_of(left);
+        let expression = ExprKind::Binary { left, op, right };
+
+        self.make_node(expression, token, start)
     }
 
-    fn advance_if(&mut self, token_type: &[TokenType]) -> Result<Option<Token>, Error> {
+    fn advance_if(&mut self, token_type: &[TokenType]) -> Result<Option<Token<'a>>, Error> {
         if self.current_token_type_is(token_type) {
-            self.advance().map(|r| Some(r))
+            self.advance().map(Some)
         } else {
             Ok(None)
         }
     }
 
-    fn advance(&mut self) -> Result<Token, Error> {
-        let next_token = loop {
-            let token = self.tokens.next_token()?;
-
-            if token.token_type != TT::SingleLineComment {
-                break token;
-            }
+    fn advance(&mut self) -> Result<Token<'a>, Error> {
+        let next_token = match self.tokens.next() {
+            Some(token) => token?,
+            // Only `Eof` is left once the tokens run out, which they also do
+            // right after a lexer error.
+            None if self.current_token_type() == TT::Eof => self.current_token.clone(),
+            None => Token::default(),
         };
-        let current_token = mem::replace(&mut self.peek_token, next_token);
-        let result = mem::replace(&mut self.current_token, current_token);
 
-        Ok(result)
+        self.previous_span = self.current_token.span;
+
+        Ok(mem::replace(&mut self.current_token, next_token))
     }
+
+    // Type of the `n`th token after the current one. A lexer error ahead reads as
+    // `Eof` here and is reported when the parser actually gets to it.
+    fn peek_token_type(&mut self, n: usize) -> TokenType {
+        match self.tokens.peek_nth(n) {
+            Some(Ok(token)) => token.token_type,
+            _ => TT::Eof,
+        }
+    }
+}
+
+fn is_significant(token: &Result<Token<'_>, LexerError>) -> bool {
+    !matches!(token, Ok(token) if matches!(token.token_type, TT::SingleLineComment | TT::MultiLineComment | TT::Error))
 }
 
 fn unescape_string(value: &str) -> Result<String, ParseStringError> {
@@ -528,11 +1008,14 @@ fn unescape_string(value: &str) -> Result<String, ParseStringError> {
                 Some('r') => result.push('\r'),
                 Some('\\') => result.push('\\'),
                 Some('"') => result.push('"'),
+                Some('\'') => result.push('\''),
+                Some('$') => result.push('$'),
                 Some('x') => {
-                    let h1 = chars.next().ok_or(ParseStringError::UnexpectedEnding)?;
-                    let h2 = chars.next().ok_or(ParseStringError::UnexpectedEnding)?;
-                    let byte = u8::from_str_radix(&format!("{h1}{h2}"), 16)?;
-                    result.push(byte as char);
+                    let byte = (0..2).try_fold(0, |byte, _| {
+                        let ch = chars.next().ok_or(ParseStringError::UnexpectedEnding)?;
+                        Ok::<_, ParseStringError>(byte * 16 + hex_digit(ch)?)
+                    })?;
+                    result.push(byte as u8 as char);
                 }
                 Some('u') => {
                     match chars.next() {
@@ -541,16 +1024,21 @@ fn unescape_string(value: &str) -> Result<String, ParseStringError> {
                         None => return Err(ParseStringError::UnexpectedEnding),
                     }
 
-                    let mut hex = String::new();
+                    // One to six hex digits, enough for every code point.
+                    let mut code = 0;
+                    let mut digits = 0;
                     loop {
                         match chars.next() {
-                            Some('}') => break,
-                            Some(ch) => hex.push(ch),
+                            Some('}') if digits > 0 => break,
+                            Some(ch) if digits < 6 => {
+                                code = code * 16 + hex_digit(ch)?;
+                                digits += 1;
+                            }
+                            Some(ch) => return Err(ParseStringError::UnexpectedChar(ch)),
                             None => return Err(ParseStringError::UnexpectedEnding)
                         }
                     }
 
-                    let code = u32::from_str_radix(&hex, 16)?;
                     let ch = char::from_u32(code).ok_or(ParseStringError::UndefinedUnicode(code))?;
 
                     result.push(ch);
@@ -570,6 +1058,56 @@ fn unescape_string(value: &str) -> Result<String, ParseStringError> {
     Ok(result)
 }
 
+fn hex_digit(ch: char) -> Result<u32, ParseStringError> {
+    ch.to_digit(16).ok_or(ParseStringError::UnexpectedChar(ch))
+}
+
+// Decodes `0x`, `0b` and `0o` prefixed and decimal integers, where single `_`
+// may separate digits, as a negative value if `negative` is set.
+fn decode_int(lexeme: &str, negative: bool) -> Result<i64, ErrorKind> {
+    let (radix, digits) = match lexeme.get(..2) {
+        Some("0x") => (16, &lexeme[2..]),
+        Some("0b") => (2, &lexeme[2..]),
+        Some("0o") => (8, &lexeme[2..]),
+        _ => (10, lexeme),
+    };
+
+    let malformed = if digits.is_empty() {
+        Some(MalformedIntError::MissingDigits)
+    } else if digits.starts_with('_') {
+        Some(MalformedIntError::LeadingSeparator)
+    } else if digits.ends_with('_') {
+        Some(MalformedIntError::TrailingSeparator)
+    } else if digits.contains("__") {
+        Some(MalformedIntError::ConsecutiveSeparators)
+    } else {
+        None
+    };
+
+    if let Some(err) = malformed {
+        return Err(ErrorKind::MalformedInt(err));
+    }
+
+    digits.chars()
+        .filter(|&ch| ch != '_')
+        .try_fold(0i64, |value, ch| {
+            let digit = ch.to_digit(radix).ok_or(ErrorKind::MalformedInt(MalformedIntError::InvalidDigit(ch)))?;
+
+            value.checked_mul(radix as i64)
+                .and_then(|value| match negative {
+                    true => value.checked_sub(digit as i64),
+                    false => value.checked_add(digit as i64),
+                })
+                .ok_or(ErrorKind::IntOverflow)
+        })
+}
+
+fn binary_operator(token_type: TT) -> Option<BinaryOperator> {
+    BINARY_OPERATORS.iter()
+        .find(|operator| operator.token_type == token_type)
+        .copied()
+}
+
 fn make_error<E: Into<Error> + 'static>(error: E, span: Option<Span>) -> Error {
     error.into().with_span(span)
 }
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::span::Span;
+
+#[cfg(test)]
+mod tests;
+
+// One-based line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineCol {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+impl fmt::Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Turns the byte offsets of spans into lines and columns. `\n`, `\r\n` and a
+/// lone `\r` all end a line.
+#[derive(Debug)]
+pub struct SourceMap<'src> {
+    source: &'src str,
+    line_starts: Vec<usize>,
+}
+
+impl<'src> SourceMap<'src> {
+    pub fn new(source: &'src str) -> Self {
+        let bytes = source.as_bytes();
+        let mut line_starts = vec![0];
+        let mut index = 0;
+
+        while index < bytes.len() {
+            match bytes[index] {
+                b'\r' if bytes.get(index + 1) == Some(&b'\n') => {
+                    index += 1;
+                    line_starts.push(index + 1);
+                }
+                b'\n' | b'\r' => line_starts.push(index + 1),
+                _ => {}
+            }
+
+            index += 1;
+        }
+
+        Self { source, line_starts }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Source text covered by the span, `None` if it is out of bounds or splits a char.
+    pub fn text(&self, span: Span) -> Option<&'src str> {
+        self.source.get(span.start..span.end())
+    }
+
+    /// Position of a byte offset with the column counted in bytes.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let (line, line_start, offset) = self.locate(offset);
+
+        LineCol::new(line + 1, offset - line_start + 1)
+    }
+
+    /// Position of a byte offset with the column counted in UTF-16 code units,
+    /// as editors speaking the Language Server Protocol expect.
+    pub fn line_col_utf16(&self, offset: usize) -> LineCol {
+        let (line, line_start, offset) = self.locate(offset);
+        let column = self.source[line_start..offset].encode_utf16().count();
+
+        LineCol::new(line + 1, column + 1)
+    }
+
+    /// Byte offset of a position whose column is counted in UTF-16 code units.
+    /// The column just past the last char of a line is its end, any later one
+    /// has no offset.
+    pub fn offset_utf16(&self, position: LineCol) -> Option<usize> {
+        let line_start = *self.line_starts.get(position.line.checked_sub(1)?)?;
+        let line_end = self.line_starts.get(position.line).copied().unwrap_or(self.source.len());
+        let content = self.source[line_start..line_end].trim_end_matches(['\n', '\r']);
+        let mut units = position.column.checked_sub(1)?;
+
+        for (index, ch) in content.char_indices() {
+            if units == 0 {
+                return Some(line_start + index);
+            }
+
+            units = units.checked_sub(ch.len_utf16())?;
+        }
+
+        (units == 0).then_some(line_start + content.len())
+    }
+
+    // Zero-based line, its start and the offset clamped to the source and moved
+    // back to the start of the char it falls into.
+    fn locate(&self, offset: usize) -> (usize, usize, usize) {
+        let mut offset = offset.min(self.source.len());
+
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+
+        (line, self.line_starts[line], offset)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::span::Span;
+
+use super::*;
+
+#[test]
+fn test_line_col() {
+    let source = "let a = 1;\nlet b = 2;\r\nb\rc\n";
+    let source_map = SourceMap::new(source);
+    let test_cases = vec![
+        (0, LineCol::new(1, 1)),
+        (4, LineCol::new(1, 5)),
+        (10, LineCol::new(1, 11)),
+        (11, LineCol::new(2, 1)),
+        (21, LineCol::new(2, 11)),
+        (22, LineCol::new(2, 12)),
+        (23, LineCol::new(3, 1)),
+        (25, LineCol::new(4, 1)),
+        (27, LineCol::new(5, 1)),
+        (100, LineCol::new(5, 1)),
+    ];
+
+    assert_eq!(5, source_map.line_count());
+
+    for tc in test_cases {
+        assert_eq!(tc.1, source_map.line_col(tc.0), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_line_col_utf16() {
+    // 'é' is two bytes and one UTF-16 unit, '😺' four bytes and two units.
+    let source = "é = \"😺\"; x\n😺";
+    let source_map = SourceMap::new(source);
+    let test_cases = vec![
+        (0, LineCol::new(1, 1), LineCol::new(1, 1)),
+        (2, LineCol::new(1, 3), LineCol::new(1, 2)),
+        (6, LineCol::new(1, 7), LineCol::new(1, 6)),
+        (10, LineCol::new(1, 11), LineCol::new(1, 8)),
+        (13, LineCol::new(1, 14), LineCol::new(1, 11)),
+        (15, LineCol::new(2, 1), LineCol::new(2, 1)),
+        (19, LineCol::new(2, 5), LineCol::new(2, 3)),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, source_map.line_col(tc.0), "{}", tc.0);
+        assert_eq!(tc.2, source_map.line_col_utf16(tc.0), "{}", tc.0);
+        assert_eq!(Some(tc.0), source_map.offset_utf16(tc.2), "{}", tc.0);
+    }
+
+    // Offsets inside a char count as its start.
+    assert_eq!(LineCol::new(1, 6), source_map.line_col_utf16(8));
+    // A column in the middle of a surrogate pair or past the line has no offset.
+    assert_eq!(None, source_map.offset_utf16(LineCol::new(1, 7)));
+    assert_eq!(None, source_map.offset_utf16(LineCol::new(2, 4)));
+    assert_eq!(None, source_map.offset_utf16(LineCol::new(3, 1)));
+}
+
+#[test]
+fn test_offset_utf16_line_ends() {
+    let source = "ab\ncd\r\nef\rg";
+    let source_map = SourceMap::new(source);
+    let test_cases = vec![
+        (LineCol::new(1, 3), Some(2)),
+        (LineCol::new(1, 4), None),
+        (LineCol::new(1, 9), None),
+        (LineCol::new(2, 3), Some(5)),
+        (LineCol::new(2, 4), None),
+        (LineCol::new(2, 5), None),
+        (LineCol::new(3, 3), Some(9)),
+        (LineCol::new(3, 4), None),
+        (LineCol::new(4, 2), Some(11)),
+        (LineCol::new(4, 3), None),
+        (LineCol::new(1, 0), None),
+    ];
+
+    for tc in test_cases {
+        assert_eq!(tc.1, source_map.offset_utf16(tc.0), "{}", tc.0);
+    }
+}
+
+#[test]
+fn test_text() {
+    let source = "let здоровье = 100;";
+    let source_map = SourceMap::new(source);
+
+    assert_eq!(Some("здоровье"), source_map.text(Span::new(4, 16)));
+    assert_eq!(Some("100"), source_map.text(Span::new(23, 3)));
+    assert_eq!(None, source_map.text(Span::new(5, 2)));
+    assert_eq!(None, source_map.text(Span::new(23, 10)));
+}
@@ -1,38 +1,40 @@
-// Pos
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Pos {
-    pub index: usize,
-    pub line: usize,
-    pub column: usize,
-}
-
-impl Default for Pos {
-    fn default() -> Self {
-        Self { index: 0, line: 1, column: 0 }
-    }
-}
-
-impl Pos {
-    pub fn new(index: usize, line: usize, column: usize) -> Self {
-        Self {
-            index,
-            line,
-            column
-        }
-    }
-}
+use std::fmt;
 
+// Byte range in the source text, see `SourceMap` for lines and columns.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Span {
-    pub position: Pos,
+    pub start: usize,
     pub length: usize,
 }
 
 impl Span {
-    pub fn new(position: Pos, length: usize) -> Self {
+    pub fn new(start: usize, length: usize) -> Self {
         Self {
-            position,
+            start,
             length,
         }
     }
+
+    /// Offset just past the last byte.
+    pub fn end(&self) -> usize {
+        self.start + self.length
+    }
+
+    /// Smallest span covering both spans and anything between them.
+    pub fn merge(&self, other: Span) -> Span {
+        let start = self.start.min(other.start);
+        let end = self.end().max(other.end());
+
+        Span::new(start, end - start)
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end()
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end())
+    }
 }
@@ -1,51 +1,111 @@
 pub mod token_type;
 
-use std::fmt;
+use std::{borrow::Cow, fmt};
 use crate::span::*;
 
 pub use token_type::TokenType;
 
-// Token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    SingleLineComment,
+    MultiLineComment,
+}
+
+// Source text between tokens, only kept by the lossless lexer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia<'src> {
+    pub kind: TriviaKind,
+    pub text: Cow<'src, str>,
+    pub span: Span,
+}
+
+impl<'src> Trivia<'src> {
+    pub fn new(kind: TriviaKind, text: impl Into<Cow<'src, str>>, span: Span) -> Self {
+        Self { kind, text: text.into(), span }
+    }
+
+    pub fn into_owned(self) -> Trivia<'static> {
+        Trivia {
+            text: Cow::Owned(self.text.into_owned()),
+            ..self
+        }
+    }
+}
+
+// Token, its text borrowed from the source until it needs to outlive it.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
+pub struct Token<'src> {
     pub token_type: TokenType,
-    pub lexeme: String,
-    pub span: Option<Span>
+    pub lexeme: Cow<'src, str>,
+    pub span: Option<Span>,
+    pub leading_trivia: Vec<Trivia<'src>>,
+    pub trailing_trivia: Vec<Trivia<'src>>,
 }
 
-impl fmt::Display for Token {
+impl fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}({})", self.token_type, self.lexeme)
     }
 }
 
-impl Default for Token {
+impl Default for Token<'_> {
     fn default() -> Self {
-        Self { token_type: TokenType::Eof, lexeme: Default::default(), span: None }
+        Self::new(TokenType::Eof, "")
     }
 }
 
-impl Token {
+impl<'src> Token<'src> {
     pub fn new(
         token_type: TokenType,
-        lexeme: String,
+        lexeme: impl Into<Cow<'src, str>>,
     ) -> Self {
         Self {
             token_type,
-            lexeme,
-            span: None
+            lexeme: lexeme.into(),
+            span: None,
+            leading_trivia: vec![],
+            trailing_trivia: vec![],
         }
     }
 
     pub fn new_with_span(
         token_type: TokenType,
-        lexeme: String,
+        lexeme: impl Into<Cow<'src, str>>,
         span: Span
     ) -> Self {
         Self {
-            token_type,
-            lexeme,
-            span: Some(span)
+            span: Some(span),
+            ..Self::new(token_type, lexeme)
+        }
+    }
+
+    /// The token with its trivia, exactly as it was written in the source.
+    pub fn full_text(&self) -> String {
+        let mut text = String::new();
+
+        for trivia in &self.leading_trivia {
+            text.push_str(&trivia.text);
+        }
+
+        text.push_str(&self.lexeme);
+
+        for trivia in &self.trailing_trivia {
+            text.push_str(&trivia.text);
+        }
+
+        text
+    }
+
+    /// Copies the borrowed text so the token can outlive the source.
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type,
+            lexeme: Cow::Owned(self.lexeme.into_owned()),
+            span: self.span,
+            leading_trivia: self.leading_trivia.into_iter().map(Trivia::into_owned).collect(),
+            trailing_trivia: self.trailing_trivia.into_iter().map(Trivia::into_owned).collect(),
         }
     }
 }
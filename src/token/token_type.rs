@@ -51,6 +51,7 @@ pub enum TokenType {
     Or,
     Not,
     Assign,
+    Arrow,
     PlusAssign,
     MinusAssign,
     MultAssign,
@@ -62,10 +63,15 @@ pub enum TokenType {
     Rparen,
     Lbrace,
     Rbrace,
+    Lbracket,
+    Rbracket,
     At,
     SingleLineComment,
+    MultiLineComment,
     New,
     Range,
+    RangeInclusive,
+    In,
     AssignPlus,
     AssignMinus,
     AssignMult,
@@ -76,12 +82,21 @@ pub enum TokenType {
     AssignBitXor,
     AssignShiftLeft,
     AssignShiftRight,
+    AssignUnsignedShiftRight,
     BitOr,
     BitAnd,
     BitXor,
     ShiftLeft,
     ShiftRight,
+    UnsignedShiftRight,
     BitNot,
+    FStringStart,
+    FStringText,
+    InterpolationStart,
+    InterpolationEnd,
+    FStringEnd,
+    // Text a recovering lexer could not make sense of.
+    Error,
 }
 
 impl fmt::Display for TokenType {
@@ -143,6 +158,7 @@ impl TokenType {
             Or => "OR",
             Not => "NOT",
             Assign => "ASSIGN",
+            Arrow => "ARROW",
             PlusAssign => "PLUS_ASSIGN",
             MinusAssign => "MINUS_ASSIGN",
             MultAssign => "MULT_ASSIGN",
@@ -154,10 +170,15 @@ impl TokenType {
             Rparen => "RPAREN",
             Lbrace => "LBRACE",
             Rbrace => "RBRANCE",
+            Lbracket => "LBRACKET",
+            Rbracket => "RBRACKET",
             At => "AT",
             SingleLineComment => "SINGLE_LINE_COMMENT",
+            MultiLineComment => "MULTI_LINE_COMMENT",
             New => "NEW",
             Range => "RANGE",
+            RangeInclusive => "RANGE_INCLUSIVE",
+            In => "IN",
             AssignPlus => "ASSIGN_PLUS",
             AssignMinus => "ASSIGN_MINUS",
             AssignMult => "ASSIGN_MULT",
@@ -168,12 +189,20 @@ impl TokenType {
             AssignBitXor => "ASSIGN_BIT_XOR",
             AssignShiftLeft => "ASSIGN_SHIFT_LEFT",
             AssignShiftRight => "ASSIGN_SHIFT_RIGHT",
+            AssignUnsignedShiftRight => "ASSIGN_UNSIGNED_SHIFT_RIGHT",
             BitOr => "BIT_OR",
             BitAnd => "BIT_AND",
             BitXor => "BIT_XOR",
             ShiftLeft => "SHIFT_LEFT",
             ShiftRight => "SHIFT_RIGHT",
+            UnsignedShiftRight => "UNSIGNED_SHIFT_RIGHT",
             BitNot => "BIT_NOT",
+            FStringStart => "FSTRING_START",
+            FStringText => "FSTRING_TEXT",
+            InterpolationStart => "INTERPOLATION_START",
+            InterpolationEnd => "INTERPOLATION_END",
+            FStringEnd => "FSTRING_END",
+            Error => "ERROR",
         }
     }
 }